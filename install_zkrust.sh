@@ -86,6 +86,11 @@ sp1up
 cargo prove --version
 echo "Sp1 Toolchain Installed"
 
+# Install jolt toolchain
+cargo install --git https://github.com/a16z/jolt --force --bins jolt
+jolt install-toolchain
+echo "Jolt Toolchain Installed"
+
 # Clone the specific directory structure from the Git repository
 
 echo "Cloning Workspaces..."
@@ -93,6 +98,10 @@ git clone "$ZKRUST_GIT_REPO_URL" "$ZKRUST_DIR/zkRust"
 
 # Copy the directory structure from the cloned repository to the .zkRust folder
 cp -r "$ZKRUST_DIR/zkRust/workspaces" "$ZKRUST_DIR/."
+# The zk_rust_io crate native runs are built with
+rm -rf "$ZKRUST_DIR/zk_rust_io"
+mkdir -p "$ZKRUST_DIR/zk_rust_io"
+cp -r "$ZKRUST_DIR/zkRust/zk_rust_io/Cargo.toml" "$ZKRUST_DIR/zkRust/zk_rust_io/src" "$ZKRUST_DIR/zk_rust_io/"
 
 # Clean up the cloned repository
 rm -rf "$ZKRUST_DIR/zkRust"
@@ -80,9 +80,24 @@ else
 fi
 cargo prove --version
 
+# Check for Jolt toolchain
+echo "Checking for Jolt toolchain..."
+if ! command -v jolt &> /dev/null; then
+    echo "Installing Jolt toolchain..."
+    cargo install --git https://github.com/a16z/jolt --force --bins jolt
+    jolt install-toolchain
+else
+    echo "Jolt toolchain already installed"
+fi
+
 # Set up workspaces directory
 echo "Setting up workspaces..."
 mkdir -p "$ZKRUST_DIR/workspaces"
 cp -r "$SCRIPT_DIR/workspaces/"* "$ZKRUST_DIR/workspaces/"
 
+# The zk_rust_io crate native runs are built with
+rm -rf "$ZKRUST_DIR/zk_rust_io"
+mkdir -p "$ZKRUST_DIR/zk_rust_io"
+cp -r "$SCRIPT_DIR/zk_rust_io/Cargo.toml" "$SCRIPT_DIR/zk_rust_io/src" "$ZKRUST_DIR/zk_rust_io/"
+
 echo "Run 'source $PROFILE' or start a new terminal session to use zkRust!" 
\ No newline at end of file
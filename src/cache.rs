@@ -0,0 +1,237 @@
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use crate::{
+    error::Result, pipeline, workspace::RunWorkspace, zkvm::ZkVm, CacheArgs, CacheCommand,
+};
+
+/// Directory of the cached builds of each zkVM, relative to the zkRust home directory
+pub const CACHE_DIR: &str = "cache";
+
+/// Total size of the cached builds, above which the least recently used ones are evicted
+pub const CACHE_MAX_SIZE: u64 = 10 * 1024 * 1024 * 1024;
+
+/// File of each cache entry whose modification time is the last time the entry was used
+const LAST_USED: &str = "last_used";
+
+/// Names of the cached build outputs
+const HOST_BIN: &str = "host";
+const PROGRAM_ELF: &str = "program.elf";
+
+/// Build outputs of a program, stored under a hash of everything the build depends on: the
+/// transformed guest and host sources, the merged `Cargo.toml` files, the precompile and GPU
+/// flags and the toolchain version.
+pub struct BuildCache {
+    key: String,
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    /// Computes the cache entry of the prepared program of `run`.
+    pub fn for_run(
+        vm: &dyn ZkVm,
+        run: &RunWorkspace,
+        home_dir: &Path,
+        precompiles: bool,
+        use_gpu: bool,
+    ) -> io::Result<Self> {
+        let layout = vm.layout();
+        let workspace_dir = run.dir().join(layout.workspace_dir);
+
+        let mut hasher = Sha256::new();
+        hash_dir(&mut hasher, &run.dir().join(layout.root), Path::new(""))?;
+        hasher.update([precompiles as u8, use_gpu as u8]);
+        hasher.update(toolchain_version(&workspace_dir)?);
+        let key = hex::encode(hasher.finalize());
+
+        let dir = home_dir.join(CACHE_DIR).join(vm.id()).join(&key);
+        Ok(Self { key, dir })
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Copies the cached build outputs into `run`. Returns `false` on a cache miss.
+    pub fn restore(&self, run: &RunWorkspace) -> io::Result<bool> {
+        let host_bin = self.dir.join(HOST_BIN);
+        if !host_bin.exists() {
+            return Ok(false);
+        }
+        fs::copy(host_bin, run.host_bin())?;
+        let program_elf = self.dir.join(PROGRAM_ELF);
+        if program_elf.exists() {
+            fs::copy(program_elf, run.program_elf())?;
+        }
+        fs::write(self.dir.join(LAST_USED), [])?;
+        Ok(true)
+    }
+
+    /// Stores the build outputs of `run`. Entries are written to a temporary directory first, so
+    /// concurrent runs never see a partial entry.
+    pub fn store(&self, run: &RunWorkspace) -> io::Result<()> {
+        let tmp_dir = self.dir.with_extension(format!("tmp-{}", run.id()));
+        fs::create_dir_all(&tmp_dir)?;
+        fs::copy(run.host_bin(), tmp_dir.join(HOST_BIN))?;
+        if run.program_elf().exists() {
+            fs::copy(run.program_elf(), tmp_dir.join(PROGRAM_ELF))?;
+        }
+        fs::write(tmp_dir.join(LAST_USED), [])?;
+
+        if let Err(e) = fs::rename(&tmp_dir, &self.dir) {
+            // Another run stored the same build first
+            fs::remove_dir_all(&tmp_dir)?;
+            if !self.dir.exists() {
+                return Err(e);
+            }
+        }
+        info!("Cached build {}", self.key);
+        Ok(())
+    }
+}
+
+/// Cached build of a zkVM, as found by `entries`.
+struct CacheEntry {
+    dir: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+/// Entries of the cache in `home_dir`, of the zkVM `vm_id` only if given. Entries still being
+/// stored by a run are skipped.
+fn entries(home_dir: &Path, vm_id: Option<&str>) -> io::Result<Vec<CacheEntry>> {
+    let cache_dir = home_dir.join(CACHE_DIR);
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for vm_dir in fs::read_dir(&cache_dir)? {
+        let vm_dir = vm_dir?;
+        if !vm_dir.file_type()?.is_dir() || vm_id.is_some_and(|id| vm_dir.file_name() != id) {
+            continue;
+        }
+        for entry in fs::read_dir(vm_dir.path())? {
+            let dir = entry?.path();
+            if !dir.is_dir() || dir.extension().is_some() {
+                continue;
+            }
+            let last_used = fs::metadata(dir.join(LAST_USED))
+                .or_else(|_| fs::metadata(&dir))?
+                .modified()?;
+            entries.push(CacheEntry {
+                size: dir_size(&dir)?,
+                dir,
+                last_used,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Evicts the least recently used builds until the cache in `home_dir` holds at most `max_size`
+/// bytes. Returns the number of bytes freed.
+pub fn evict(home_dir: &Path, max_size: u64) -> io::Result<u64> {
+    let mut entries = entries(home_dir, None)?;
+    let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+    entries.sort_by_key(|entry| entry.last_used);
+
+    let mut freed = 0;
+    for entry in entries {
+        if size <= max_size {
+            break;
+        }
+        fs::remove_dir_all(&entry.dir)?;
+        info!("Evicted cached build {}", entry.dir.display());
+        size -= entry.size;
+        freed += entry.size;
+    }
+    Ok(freed)
+}
+
+/// Removes the cached builds in `home_dir`, of the zkVM `vm_id` only if given. Returns the number
+/// of builds removed and the bytes freed.
+pub fn clean(home_dir: &Path, vm_id: Option<&str>) -> io::Result<(usize, u64)> {
+    let entries = entries(home_dir, vm_id)?;
+    for entry in &entries {
+        fs::remove_dir_all(&entry.dir)?;
+    }
+    Ok((entries.len(), entries.iter().map(|entry| entry.size).sum()))
+}
+
+/// Runs a `cache` command on the cache of the zkRust home directory.
+pub fn command(args: &CacheArgs) -> Result<()> {
+    let home_dir = pipeline::zkrust_home()?;
+    match &args.command {
+        CacheCommand::Clean { system } => {
+            let vm_id = system.map(|system| system.zkvm().id());
+            let (removed, freed) = clean(&home_dir, vm_id)?;
+            info!(
+                "Removed {} cached builds from {}, {} MB freed",
+                removed,
+                home_dir.join(CACHE_DIR).display(),
+                freed / (1024 * 1024)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn dir_size(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+/// Hashes the relative path and contents of every file in `dir`, in a stable order, skipping
+/// build outputs.
+fn hash_dir(hasher: &mut Sha256, dir: &Path, relative: &Path) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        if name == "target" {
+            continue;
+        }
+        let relative = relative.join(&name);
+        if entry.file_type()?.is_dir() {
+            hash_dir(hasher, &entry.path(), &relative)?;
+        } else {
+            hasher.update(relative.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            let contents = fs::read(entry.path())?;
+            hasher.update((contents.len() as u64).to_le_bytes());
+            hasher.update(contents);
+        }
+    }
+    Ok(())
+}
+
+/// Version of the Rust toolchain selected in `workspace_dir`, including its `rust-toolchain.toml`.
+fn toolchain_version(workspace_dir: &Path) -> io::Result<Vec<u8>> {
+    let output = Command::new("rustc")
+        .arg("--version")
+        .arg("--verbose")
+        .current_dir(workspace_dir)
+        .output()?;
+    if !output.status.success() {
+        warn!(
+            "Failed to read toolchain version: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,306 @@
+use log::{error, info};
+use serde::Serialize;
+use std::{fmt::Write as _, fs, io, path::Path, time::Duration};
+
+use crate::{
+    error::{Result, ZkRustError},
+    input::ProgramInput,
+    native,
+    pipeline::{self, ProveOutcome},
+    telemetry::TelemetryData,
+    zkvm::{ProofMode, ZkVm},
+    CompareArgs,
+};
+
+/// Report of a comparison, saved as Markdown and JSON to the output directory.
+pub const COMPARISON_MARKDOWN_PATH: &str = "comparison.md";
+pub const COMPARISON_JSON_PATH: &str = "comparison.json";
+
+/// Results of one backend in a comparison.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendResult {
+    /// Identifier of the zkVM, e.g. `sp1`.
+    pub backend: &'static str,
+    pub proof_mode: ProofMode,
+    pub cycles: Option<u64>,
+    pub num_segments: Option<usize>,
+    pub core_proof_size: Option<usize>,
+    pub recursive_proof_size: Option<usize>,
+    pub wrap_proof_size: Option<usize>,
+    /// Time spent by the host proving and verifying each stage of the proof, `None` for the
+    /// stages it did not run.
+    pub core_prove_duration: Option<Duration>,
+    pub core_verify_duration: Option<Duration>,
+    pub compress_prove_duration: Option<Duration>,
+    pub compress_verify_duration: Option<Duration>,
+    pub wrap_prove_duration: Option<Duration>,
+    pub wrap_verify_duration: Option<Duration>,
+    pub peak_memory_kb: Option<u64>,
+    /// Public values decoded to JSON, `None` if the backend failed or they could not be decoded.
+    pub output: Option<serde_json::Value>,
+    /// Whether the public values match the ones committed natively, with `--check-native`.
+    pub native_match: Option<bool>,
+    /// Error of a failed backend.
+    pub error: Option<String>,
+}
+
+/// Results of all the backends of a comparison, in the order they ran.
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub program: String,
+    pub backends: Vec<BackendResult>,
+    /// Whether every backend committed the same decoded public values.
+    pub outputs_match: bool,
+}
+
+/// Proves the program in `args.guest_path` with each backend of `args.backends` in turn, with
+/// the same inputs, and saves a report of their metrics. Fails if a backend fails or if their
+/// decoded public values differ, after saving the report. With `args.check_native`, the program
+/// is first run natively and the public values of each backend must match its commits.
+pub fn compare(args: &CompareArgs) -> Result<()> {
+    let modes = args
+        .backends
+        .iter()
+        .map(|system| proof_mode(system.zkvm(), args.proof_mode))
+        .collect::<Result<Vec<_>>>()?;
+    for system in &args.backends {
+        pipeline::check_support(system.zkvm(), Path::new(&args.guest_path), args.mock)?;
+    }
+
+    let native = if args.check_native {
+        let input = args
+            .input_path
+            .as_deref()
+            .map(|path| ProgramInput::new(path, args.input_format))
+            .transpose()?;
+        Some(native::run(
+            Path::new(&args.guest_path),
+            input.as_ref(),
+            &pipeline::zkrust_home()?,
+            &pipeline::proof_data_dir(Path::new(&args.proof_data_directory_path))?,
+            args.keep_workspace,
+        )?)
+    } else {
+        None
+    };
+
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for (system, proof_mode) in args.backends.iter().zip(modes) {
+        let vm = system.zkvm();
+        info!("Comparing {} {} proof", vm.name(), proof_mode);
+        let result = match pipeline::prove(&args.prove_config(*system, proof_mode)) {
+            Ok(outcome) => {
+                let native_check = native
+                    .as_ref()
+                    .map(|native| native.check(vm, &outcome.artifacts.public_input));
+                let mut result = BackendResult::new(vm, proof_mode, outcome);
+                if let Some(check) = native_check {
+                    result.native_match = Some(check.is_ok());
+                    if let Err(e) = check {
+                        error!("{}", e);
+                        failures.push(e);
+                    }
+                }
+                result
+            }
+            Err(e) => {
+                error!("{} failed: {}", vm.name(), e);
+                let result = BackendResult::failed(vm, proof_mode, &e);
+                failures.push(e);
+                result
+            }
+        };
+        results.push(result);
+    }
+
+    let comparison = Comparison::new(&args.guest_path, results);
+    comparison.save(Path::new(&args.output_path))?;
+
+    if !failures.is_empty() {
+        return Err(failures.remove(0));
+    }
+    if !comparison.outputs_match {
+        return Err(ZkRustError::OutputMismatch(
+            "the backends committed different public values".to_string(),
+        ));
+    }
+    info!("Public values of all backends match");
+    Ok(())
+}
+
+/// Proof mode `vm` runs with: `requested` if given, otherwise the default proof mode or, if `vm`
+/// does not support it, the first one it does.
+fn proof_mode(vm: &dyn ZkVm, requested: Option<ProofMode>) -> Result<ProofMode> {
+    let modes = vm.proof_modes();
+    match requested {
+        Some(mode) if modes.contains(&mode) => Ok(mode),
+        Some(mode) => Err(ZkRustError::UnsupportedProofMode {
+            system: vm.name(),
+            mode,
+        }),
+        None if modes.contains(&ProofMode::default()) => Ok(ProofMode::default()),
+        None => Ok(modes[0]),
+    }
+}
+
+impl BackendResult {
+    fn new(vm: &dyn ZkVm, proof_mode: ProofMode, outcome: ProveOutcome) -> Self {
+        let mut result = Self::empty(vm, proof_mode);
+        result.output = outcome.output;
+        if let Some(telemetry) = &outcome.telemetry {
+            result.record(telemetry);
+        }
+        result
+    }
+
+    fn failed(vm: &dyn ZkVm, proof_mode: ProofMode, error: &ZkRustError) -> Self {
+        let mut result = Self::empty(vm, proof_mode);
+        result.error = Some(error.to_string());
+        result
+    }
+
+    fn empty(vm: &dyn ZkVm, proof_mode: ProofMode) -> Self {
+        Self {
+            backend: vm.id(),
+            proof_mode,
+            cycles: None,
+            num_segments: None,
+            core_proof_size: None,
+            recursive_proof_size: None,
+            wrap_proof_size: None,
+            core_prove_duration: None,
+            core_verify_duration: None,
+            compress_prove_duration: None,
+            compress_verify_duration: None,
+            wrap_prove_duration: None,
+            wrap_verify_duration: None,
+            peak_memory_kb: None,
+            output: None,
+            native_match: None,
+            error: None,
+        }
+    }
+
+    fn record(&mut self, telemetry: &TelemetryData) {
+        let zk_metrics = &telemetry.zk_metrics;
+        let timing = &telemetry.timing;
+        self.cycles = zk_metrics.cycles;
+        self.num_segments = zk_metrics.num_segments;
+        self.core_proof_size = zk_metrics.core_proof_size;
+        self.recursive_proof_size = zk_metrics.recursive_proof_size;
+        self.wrap_proof_size = zk_metrics.wrap_proof_size;
+        self.core_prove_duration = timing.core_prove_duration;
+        self.core_verify_duration = timing.core_verify_duration;
+        self.compress_prove_duration = timing.compress_prove_duration;
+        self.compress_verify_duration = timing.compress_verify_duration;
+        self.wrap_prove_duration = timing.wrap_prove_duration;
+        self.wrap_verify_duration = timing.wrap_verify_duration;
+        self.peak_memory_kb =
+            (telemetry.resources.samples > 0).then_some(telemetry.resources.max_memory_kb);
+    }
+}
+
+impl Comparison {
+    fn new(program: &str, backends: Vec<BackendResult>) -> Self {
+        // Failed backends have no output, which matches no other
+        let outputs_match = backends.iter().all(|result| result.output.is_some())
+            && backends
+                .windows(2)
+                .all(|pair| pair[0].output == pair[1].output);
+        Self {
+            program: program.to_string(),
+            backends,
+            outputs_match,
+        }
+    }
+
+    /// Saves the report to `output_dir` as a Markdown table and as JSON.
+    pub fn save(&self, output_dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(output_dir)?;
+        let markdown_path = output_dir.join(COMPARISON_MARKDOWN_PATH);
+        fs::write(&markdown_path, self.to_markdown())?;
+        fs::write(
+            output_dir.join(COMPARISON_JSON_PATH),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        info!("Comparison saved to: {}", markdown_path.display());
+        Ok(())
+    }
+
+    /// Tables of the metrics and of the prove and verify times of each stage of each backend,
+    /// followed by their decoded public values.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# Comparison of {}\n\n", self.program);
+        markdown.push_str(
+            "| Backend | Proof mode | Cycles | Segments | Core proof size | Compressed proof size \
+             | Wrapped proof size | Peak memory |\n",
+        );
+        markdown.push_str("|---|---|---|---|---|---|---|---|\n");
+        for result in &self.backends {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} | {} | {} |",
+                result.backend,
+                result.proof_mode,
+                cell(result.cycles),
+                cell(result.num_segments),
+                cell(result.core_proof_size.map(bytes)),
+                cell(result.recursive_proof_size.map(bytes)),
+                cell(result.wrap_proof_size.map(bytes)),
+                cell(result.peak_memory_kb.map(|kb| format!("{} MB", kb / 1024))),
+            );
+        }
+
+        markdown.push_str(
+            "\n| Backend | Core prove time | Core verify time | Compressed prove time \
+             | Compressed verify time | Wrap prove time | Wrap verify time |\n",
+        );
+        markdown.push_str("|---|---|---|---|---|---|---|\n");
+        for result in &self.backends {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                result.backend,
+                cell(result.core_prove_duration.map(time)),
+                cell(result.core_verify_duration.map(time)),
+                cell(result.compress_prove_duration.map(time)),
+                cell(result.compress_verify_duration.map(time)),
+                cell(result.wrap_prove_duration.map(time)),
+                cell(result.wrap_verify_duration.map(time)),
+            );
+        }
+
+        let _ = writeln!(
+            markdown,
+            "\nPublic values match: {}\n",
+            if self.outputs_match { "yes" } else { "no" }
+        );
+        for result in &self.backends {
+            let mut output = match (&result.output, &result.error) {
+                (Some(output), _) => format!("`{}`", output),
+                (None, Some(error)) => format!("failed: {}", error),
+                (None, None) => "not decoded".to_string(),
+            };
+            match result.native_match {
+                Some(true) => output.push_str(", matches the native run"),
+                Some(false) => output.push_str(", differs from the native run"),
+                None => {}
+            }
+            let _ = writeln!(markdown, "- {}: {}", result.backend, output);
+        }
+        markdown
+    }
+}
+
+fn time(duration: Duration) -> String {
+    format!("{:.2?}", duration)
+}
+
+fn bytes(size: usize) -> String {
+    format!("{} B", size)
+}
+
+fn cell(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
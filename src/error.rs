@@ -0,0 +1,128 @@
+use std::io;
+use thiserror::Error;
+
+use crate::zkvm::ProofMode;
+
+/// Exit code of the hosts when the proof they generated fails verification. Panics of the hosts
+/// exit with 101 and are reported as generic prover failures.
+pub const HOST_VERIFICATION_FAILED: i32 = 103;
+/// Exit code of the hosts when the ELF of the program could not be generated.
+pub const HOST_ELF_FAILED: i32 = 102;
+
+/// Result of the zkRust library functions.
+pub type Result<T> = std::result::Result<T, ZkRustError>;
+
+#[derive(Debug, Error)]
+pub enum ZkRustError {
+    /// The program directory does not have the structure described in the README.
+    #[error("invalid project layout: {0}")]
+    InvalidLayout(String),
+
+    /// The program could not be parsed or rewritten for a zkVM.
+    #[error("failed to transform program: {0}")]
+    Transform(#[from] syn::Error),
+
+    /// Building the guest or host failed.
+    #[error("{system} program build failed with exit code {}", display_code(*.code))]
+    Build {
+        system: &'static str,
+        code: Option<i32>,
+    },
+
+    /// The host exited with an error while generating a proof.
+    #[error("{system} proof generation failed: {}", prover_failure(*.code))]
+    Prover {
+        system: &'static str,
+        code: Option<i32>,
+    },
+
+    /// The host exited with an error while executing the program.
+    #[error("{system} execution failed with exit code {}", display_code(*.code))]
+    Execution {
+        system: &'static str,
+        code: Option<i32>,
+    },
+
+    /// A proof or its public values did not verify.
+    #[error("proof verification failed: {0}")]
+    Verification(String),
+
+    /// The proof could not be submitted to or verified on Aligned.
+    #[error("error submitting proof to Aligned: {0}")]
+    Aligned(String),
+
+    /// The zkVM cannot generate proofs of the requested proof mode.
+    #[error("{system} does not support the {mode} proof mode")]
+    UnsupportedProofMode {
+        system: &'static str,
+        mode: ProofMode,
+    },
+
+    /// The zkVM does not support a feature the run requires, e.g. mock proofs.
+    #[error("{system} does not support {feature}")]
+    Unsupported {
+        system: &'static str,
+        feature: &'static str,
+    },
+
+    /// The saved proof could not be exported for the EVM verifier contracts.
+    #[error("failed to export EVM verifier: {0}")]
+    Export(String),
+
+    /// The file given with `--input` could not be read or does not match the program.
+    #[error("{0}")]
+    Input(String),
+
+    /// The public values could not be decoded with the recorded commit types.
+    #[error("failed to decode output: {0}")]
+    Output(String),
+
+    /// Runs of the same program committed different public values.
+    #[error("public values do not match: {0}")]
+    OutputMismatch(String),
+
+    /// The home or current directory could not be located.
+    #[error("failed to locate {0} directory")]
+    MissingDirectory(&'static str),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl ZkRustError {
+    /// Exit code of the zkRust process for this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ZkRustError::Io(_) | ZkRustError::MissingDirectory(_) => 1,
+            ZkRustError::InvalidLayout(_) => 2,
+            ZkRustError::Transform(_) => 3,
+            ZkRustError::Build { .. } => 4,
+            ZkRustError::Prover { code, .. } => match *code {
+                Some(HOST_ELF_FAILED) => 102,
+                Some(HOST_VERIFICATION_FAILED) => 103,
+                _ => 5,
+            },
+            ZkRustError::Execution { .. } => 6,
+            ZkRustError::Verification(_) => 7,
+            ZkRustError::Aligned(_) => 8,
+            ZkRustError::UnsupportedProofMode { .. } => 9,
+            ZkRustError::Export(_) => 10,
+            ZkRustError::Input(_) => 11,
+            ZkRustError::Output(_) => 12,
+            ZkRustError::OutputMismatch(_) => 13,
+            ZkRustError::Unsupported { .. } => 14,
+        }
+    }
+}
+
+fn display_code(code: Option<i32>) -> String {
+    code.map_or_else(|| "none".to_string(), |code| code.to_string())
+}
+
+fn prover_failure(code: Option<i32>) -> String {
+    match code {
+        Some(HOST_ELF_FAILED) => "ELF file generation failed".to_string(),
+        Some(HOST_VERIFICATION_FAILED) => "the generated proof could not be verified".to_string(),
+        code => format!("unknown error, exit code {}", display_code(code)),
+    }
+}
@@ -0,0 +1,132 @@
+use ethers::abi::{self, Token};
+use log::info;
+use std::{fs, path::Path};
+
+use crate::{
+    error::{Result, ZkRustError},
+    zkvm::ZkVm,
+    ExportEvmArgs,
+};
+
+/// Function of the exported verifier contract the calldata is encoded for
+pub const VERIFY_FUNCTION: &str = "verify(bytes,bytes)";
+
+/// Files of the exported Foundry project, relative to its directory
+pub const FOUNDRY_TOML_PATH: &str = "foundry.toml";
+pub const CONTRACT_PATH: &str = "src/ZkRustVerifier.sol";
+pub const TEST_PATH: &str = "test/ZkRustVerifier.t.sol";
+pub const CALLDATA_PATH: &str = "calldata.hex";
+
+/// Template markers replaced by the backends
+pub const PROGRAM_ID: &str = "{{PROGRAM_ID}}";
+pub const PUBLIC_VALUES: &str = "{{PUBLIC_VALUES}}";
+pub const PROOF: &str = "{{PROOF}}";
+
+/// Foundry project checking a wrapped proof with the EVM verifier contracts of a zkVM.
+pub struct EvmExport {
+    /// Repositories installed with `forge install`.
+    pub dependencies: &'static [&'static str],
+    pub remappings: &'static [&'static str],
+    /// Contract pinned to the verifying key or image ID of the program, calling the verifier of
+    /// the zkVM.
+    pub contract: String,
+    /// Foundry test deploying the verifier and checking the proof.
+    pub test: String,
+    pub public_values: Vec<u8>,
+    /// Proof as passed to the verifier of the zkVM.
+    pub proof: Vec<u8>,
+}
+
+impl EvmExport {
+    /// ABI-encoded call of `verify(publicValues, proof)` on the exported contract.
+    pub fn calldata(&self) -> Vec<u8> {
+        let mut calldata = ethers::utils::id(VERIFY_FUNCTION).to_vec();
+        calldata.extend(abi::encode(&[
+            Token::Bytes(self.public_values.clone()),
+            Token::Bytes(self.proof.clone()),
+        ]));
+        calldata
+    }
+}
+
+/// Exports the EVM verifier, calldata and Foundry test of the wrapped proof saved in the proof
+/// data directory of `vm`.
+pub fn export(vm: &dyn ZkVm, args: &ExportEvmArgs) -> Result<()> {
+    let artifacts = vm
+        .artifacts()
+        .paths(Path::new(&args.proof_data_directory_path));
+    let metrics = vm.read_metrics(&artifacts.metrics)?;
+    if metrics.is_mock() {
+        return Err(ZkRustError::Export(format!(
+            "{} proof in {} is a mock proof",
+            vm.name(),
+            artifacts.dir.display()
+        )));
+    }
+    let proof_mode = metrics.proof_mode();
+    if !proof_mode.is_wrapped() {
+        return Err(ZkRustError::Export(format!(
+            "{} proof in {} is a {} proof, generate a wrapped proof with `--proof-mode groth16` first",
+            vm.name(),
+            artifacts.dir.display(),
+            proof_mode
+        )));
+    }
+
+    let export = vm.export_evm(&artifacts, proof_mode)?;
+    let out_dir = Path::new(&args.output_path);
+    fs::create_dir_all(out_dir.join("src"))?;
+    fs::create_dir_all(out_dir.join("test"))?;
+    fs::write(out_dir.join(FOUNDRY_TOML_PATH), foundry_toml(&export))?;
+    fs::write(out_dir.join(CONTRACT_PATH), &export.contract)?;
+    fs::write(out_dir.join(TEST_PATH), &export.test)?;
+    fs::write(
+        out_dir.join(CALLDATA_PATH),
+        format!("0x{}", hex::encode(export.calldata())),
+    )?;
+
+    info!(
+        "{} {} verifier exported to: {}",
+        vm.name(),
+        proof_mode,
+        out_dir.display()
+    );
+    info!(
+        "Check the proof with: cd {} && forge install --no-git {} && forge test",
+        out_dir.display(),
+        export.dependencies.join(" ")
+    );
+    Ok(())
+}
+
+/// Renders `template`, replacing each marker with its value.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |rendered, (marker, value)| {
+            rendered.replace(marker, value)
+        })
+}
+
+/// Solidity hex literal of `bytes`.
+pub fn hex_literal(bytes: &[u8]) -> String {
+    format!("hex\"{}\"", hex::encode(bytes))
+}
+
+/// Decodes a `0x`-prefixed hex string saved by the host.
+pub fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| ZkRustError::Export(format!("invalid hex in calldata: {}", e)))
+}
+
+fn foundry_toml(export: &EvmExport) -> String {
+    let remappings = export
+        .remappings
+        .iter()
+        .map(|remapping| format!("    \"{}\",\n", remapping))
+        .collect::<String>();
+    format!(
+        "[profile.default]\nsrc = \"src\"\ntest = \"test\"\nlibs = [\"lib\"]\nremappings = [\n{}]\n",
+        remappings
+    )
+}
@@ -0,0 +1,125 @@
+use clap::ValueEnum;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::{Result, ZkRustError},
+    transform::Program,
+    utils,
+};
+
+/// Key of the array holding the values in TOML input files, as TOML documents must be tables
+pub const TOML_INPUTS_KEY: &str = "inputs";
+
+/// Format of an input file given with `--input`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    /// Array with one element per value.
+    Json,
+    /// `inputs` array with one element per value.
+    Toml,
+    /// Concatenated bincode-serialized values.
+    Bincode,
+    /// Hex encoding of concatenated bincode-serialized values.
+    Hex,
+}
+
+impl InputFormat {
+    /// Argument passing the format to the host.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InputFormat::Json => "json",
+            InputFormat::Toml => "toml",
+            InputFormat::Bincode => "bincode",
+            InputFormat::Hex => "hex",
+        }
+    }
+
+    /// Format of `path` from its extension: `.json`, `.toml`, `.bin` or `.hex`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(InputFormat::Json),
+            "toml" => Some(InputFormat::Toml),
+            "bin" | "bincode" => Some(InputFormat::Bincode),
+            "hex" => Some(InputFormat::Hex),
+            _ => None,
+        }
+    }
+}
+
+/// File holding the values read by the guest with `zk_rust_io::read()`, written to the guest by
+/// the host at runtime instead of the values written by `input()`.
+#[derive(Debug, Clone)]
+pub struct ProgramInput {
+    /// Absolute path of the file, as the host runs in its workspace.
+    pub path: PathBuf,
+    pub format: InputFormat,
+}
+
+impl ProgramInput {
+    /// Resolves the input file at `path`, in `format` or the format of its extension.
+    pub fn new(path: &Path, format: Option<InputFormat>) -> Result<Self> {
+        let format = format
+            .or_else(|| InputFormat::from_path(path))
+            .ok_or_else(|| {
+                ZkRustError::Input(format!(
+                    "unknown format of {}, set it with --input-format",
+                    path.display()
+                ))
+            })?;
+        let path = fs::canonicalize(path)
+            .map_err(|e| ZkRustError::Input(format!("failed to open {}: {}", path.display(), e)))?;
+        Ok(Self { path, format })
+    }
+
+    /// Checks that the file holds one value per `zk_rust_io::read()` of `program`. Bincode values
+    /// are only checked by the host, when deserialized.
+    pub fn check(&self, program: &Program) -> Result<()> {
+        let reads = program.read_types(utils::IO_READ)?.len();
+        let contents = || fs::read_to_string(&self.path);
+        let values = match self.format {
+            InputFormat::Json => {
+                let value: serde_json::Value =
+                    serde_json::from_str(&contents()?).map_err(|e| self.invalid(e.to_string()))?;
+                value.as_array().map(Vec::len)
+            }
+            InputFormat::Toml => {
+                let value: toml::Table =
+                    toml::from_str(&contents()?).map_err(|e| self.invalid(e.to_string()))?;
+                value
+                    .get(TOML_INPUTS_KEY)
+                    .and_then(toml::Value::as_array)
+                    .map(Vec::len)
+            }
+            InputFormat::Hex => {
+                hex::decode(contents()?.trim().trim_start_matches("0x"))
+                    .map_err(|e| self.invalid(e.to_string()))?;
+                return Ok(());
+            }
+            InputFormat::Bincode => return Ok(()),
+        };
+
+        match values {
+            Some(values) if values == reads => Ok(()),
+            Some(values) => Err(self.invalid(format!(
+                "{} values given, the program reads {}",
+                values, reads
+            ))),
+            None if self.format == InputFormat::Toml => {
+                Err(self.invalid(format!("expected an `{}` array", TOML_INPUTS_KEY)))
+            }
+            None => Err(self.invalid("expected an array".to_string())),
+        }
+    }
+
+    fn invalid(&self, reason: String) -> ZkRustError {
+        ZkRustError::Input(format!(
+            "invalid {} input {}: {}",
+            self.format.as_str(),
+            self.path.display(),
+            reason
+        ))
+    }
+}
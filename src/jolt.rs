@@ -0,0 +1,385 @@
+use aligned_sdk::core::types::ProvingSystemId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+use syn::parse_quote;
+
+use crate::{
+    error::{Result, ZkRustError},
+    evm::EvmExport,
+    input::ProgramInput,
+    output::OutputEncoding,
+    precompiles::PrecompilePatch,
+    telemetry::TelemetryCollector,
+    transform::{IoRewrite, Program},
+    utils,
+    zkvm::{
+        host_input_args, ArtifactPaths, Artifacts, Metrics, ProofMode, ProverOptions, SyscallStats,
+        WorkspaceLayout, ZkVm, HOST_EXECUTE_MODE,
+    },
+};
+
+/// Metrics written by the Jolt host, with the fields of the SP1 metrics. Jolt proofs are not
+/// recursively compressed nor wrapped, the compressed proof is the core proof.
+#[derive(Default, Serialize, Deserialize)]
+pub struct JoltMetrics {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub core_proof_size: usize,
+    pub recursive_proof_size: usize,
+    pub core_prove_duration: Duration,
+    pub core_verify_duration: Duration,
+    pub compress_prove_duration: Duration,
+    pub compress_verify_duration: Duration,
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    #[serde(default)]
+    pub wrap_proof_size: usize,
+    #[serde(default)]
+    pub wrap_prove_duration: Duration,
+    #[serde(default)]
+    pub wrap_verify_duration: Duration,
+    #[serde(default)]
+    pub mock: bool,
+    /// Always empty, Jolt does not report the syscalls of an execution
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    #[serde(default)]
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+impl JoltMetrics {
+    pub fn record(&self, telemetry: &TelemetryCollector) {
+        telemetry.record_zk_metrics(
+            Some(self.cycles),
+            Some(self.num_segments),
+            Some(self.core_proof_size),
+            Some(self.recursive_proof_size),
+        );
+        telemetry.record_proof_timings(
+            self.core_prove_duration,
+            self.core_verify_duration,
+            None,
+            None,
+        );
+        telemetry.record_syscalls(&self.syscalls, &self.precompiles);
+    }
+}
+
+/// Jolt workspace directories. The host is the root of the workspace and compiles the guest,
+/// one of its members, when it runs.
+pub const JOLT_WORKSPACE_ROOT: &str = "workspaces/jolt";
+pub const JOLT_BASE_DIR: &str = "workspaces/base_files/jolt";
+pub const JOLT_HOST_DIR: &str = "workspaces/jolt";
+pub const JOLT_SRC_DIR: &str = "workspaces/jolt/guest";
+pub const JOLT_GUEST_MAIN: &str = "workspaces/jolt/guest/src/main.rs";
+pub const JOLT_HOST_MAIN: &str = "workspaces/jolt/src/main.rs";
+pub const JOLT_BASE_GUEST_CARGO_TOML: &str = "workspaces/base_files/jolt/cargo_guest";
+pub const JOLT_BASE_HOST_CARGO_TOML: &str = "workspaces/base_files/jolt/cargo_host";
+pub const JOLT_BASE_HOST: &str = "workspaces/base_files/jolt/host";
+pub const JOLT_GUEST_CARGO_TOML: &str = "workspaces/jolt/guest/Cargo.toml";
+pub const JOLT_HOST_CARGO_TOML: &str = "workspaces/jolt/Cargo.toml";
+
+// Build outputs, relative to the cargo target directory
+pub const JOLT_HOST_BIN: &str = "release/host";
+
+// Proof data generation paths, relative to the proof data directory
+pub const JOLT_PROOF_DATA_DIR: &str = "jolt";
+pub const JOLT_ELF_PATH: &str = "jolt.elf";
+pub const JOLT_PROOF_PATH: &str = "jolt.proof";
+pub const JOLT_PUB_INPUT_PATH: &str = "jolt.pub";
+pub const JOLT_METRICS_PATH: &str = "jolt_metrics.json";
+pub const JOLT_EXECUTION_REPORT_PATH: &str = "jolt_execution.json";
+pub const JOLT_CALLDATA_PATH: &str = "jolt_calldata.json";
+pub const JOLT_COMMIT_TYPES_PATH: &str = "jolt_commit_types.json";
+pub const JOLT_OUTPUT_PATH: &str = "jolt_output.json";
+
+static JOLT_LAYOUT: WorkspaceLayout = WorkspaceLayout {
+    root: JOLT_WORKSPACE_ROOT,
+    base_dir: JOLT_BASE_DIR,
+    workspace_dir: JOLT_HOST_DIR,
+    guest_dir: JOLT_SRC_DIR,
+    guest_main: JOLT_GUEST_MAIN,
+    guest_cargo_toml: JOLT_GUEST_CARGO_TOML,
+    host_dir: JOLT_HOST_DIR,
+    host_main: JOLT_HOST_MAIN,
+    host_cargo_toml: JOLT_HOST_CARGO_TOML,
+    base_guest_cargo_toml: JOLT_BASE_GUEST_CARGO_TOML,
+    base_host_cargo_toml: JOLT_BASE_HOST_CARGO_TOML,
+    base_host: JOLT_BASE_HOST,
+    program_elf: None,
+    host_bin: JOLT_HOST_BIN,
+};
+
+static JOLT_ARTIFACTS: Artifacts = Artifacts {
+    dir: JOLT_PROOF_DATA_DIR,
+    proof: JOLT_PROOF_PATH,
+    program: JOLT_ELF_PATH,
+    public_input: JOLT_PUB_INPUT_PATH,
+    metrics: JOLT_METRICS_PATH,
+    execution_report: JOLT_EXECUTION_REPORT_PATH,
+    calldata: JOLT_CALLDATA_PATH,
+    commit_types: JOLT_COMMIT_TYPES_PATH,
+    output: JOLT_OUTPUT_PATH,
+};
+
+/// Jolt header added to programs for generating proofs of their execution. Jolt proves calls of
+/// a `#[jolt::provable]` function, which runs `main()` with the values written by the host and
+/// returns the values it commits.
+pub const JOLT_GUEST_PROGRAM_HEADER: &str = r#"
+pub mod zkrust {
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::{cell::RefCell, io::Cursor};
+
+    thread_local! {
+        static INPUT: RefCell<Cursor<Vec<u8>>> = RefCell::new(Cursor::new(Vec::new()));
+        static OUTPUT: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+    }
+
+    #[jolt::provable(max_input_size = 65536, max_output_size = 65536)]
+    pub fn method(input: Vec<u8>) -> Vec<u8> {
+        INPUT.with(|cell| *cell.borrow_mut() = Cursor::new(input));
+        OUTPUT.with(|cell| cell.borrow_mut().clear());
+        super::main();
+        OUTPUT.with(|cell| cell.take())
+    }
+
+    pub fn read<T: DeserializeOwned>() -> T {
+        INPUT.with(|cell| {
+            bincode::deserialize_from(&mut *cell.borrow_mut()).expect("Failed to read input")
+        })
+    }
+
+    pub fn commit<T: Serialize>(value: &T) {
+        OUTPUT.with(|cell| {
+            bincode::serialize_into(&mut *cell.borrow_mut(), value).expect("Failed to commit")
+        })
+    }
+}
+"#;
+
+/// Jolt crates the program manifest cannot override in the guest and host manifests
+pub const JOLT_PINNED_CRATES: [&str; 1] = ["jolt-sdk"];
+
+/// Jolt User I/O
+// Host
+pub const JOLT_HOST_WRITE: &str = "stdin.write";
+pub const JOLT_HOST_READ: &str = "public_values.read";
+
+// Guest
+pub const JOLT_IO_READ: &str = "crate::zkrust::read";
+pub const JOLT_IO_COMMIT: &str = "crate::zkrust::commit";
+
+pub fn prepare_host(program: &Program, host_dir: &PathBuf, host_main: &PathBuf) -> Result<()> {
+    let mut host_program = program.host_items();
+    let contents = fs::read_to_string(host_dir)?;
+
+    host_program.push_str(&contents);
+
+    // Insert input body, replacing zkRust::write
+    let input = program.input_body(&[IoRewrite::call(utils::IO_WRITE, JOLT_HOST_WRITE)])?;
+    let host_program = host_program.replace(utils::HOST_INPUT, &input);
+    // Insert output body, replacing zkRust::out()
+    let output = program.output_body(&[IoRewrite::call(utils::IO_OUT, JOLT_HOST_READ)])?;
+    let host_program = host_program.replace(utils::HOST_OUTPUT, &output);
+    // Insert writing of the values given with `--input`
+    let read_inputs = utils::read_inputs_body(
+        program,
+        |ty| parse_quote!(stdin.write(&inputs.next::<#ty>());),
+    );
+    let host_program = host_program.replace(utils::HOST_READ_INPUTS, &read_inputs);
+
+    // Write to host
+    let mut file = fs::File::create(host_main)?;
+    file.write_all(host_program.as_bytes())?;
+    Ok(())
+}
+
+/// Build the Jolt host, which compiles the guest when it runs
+pub fn build_jolt_program(workspace_dir: &Path, target_dir: &Path) -> io::Result<ExitStatus> {
+    Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .arg("--bin")
+        .arg("host")
+        .env("CARGO_TARGET_DIR", target_dir)
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Generates a Jolt proof and ELF, saving them to `output_dir`
+pub fn generate_jolt_proof(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    options: &ProverOptions,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(options.proof_mode.as_str())
+        .args(host_input_args(options.input.as_ref()))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Traces the program with the Jolt tracer without generating a proof
+pub fn execute_jolt_program(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    input: Option<&ProgramInput>,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(HOST_EXECUTE_MODE)
+        .args(host_input_args(input))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+pub fn read_metrics(metrics_path: &Path) -> io::Result<JoltMetrics> {
+    let metrics_str = fs::read_to_string(metrics_path)?;
+    serde_json::from_str(&metrics_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub struct Jolt;
+
+impl ZkVm for Jolt {
+    fn name(&self) -> &'static str {
+        "Jolt"
+    }
+
+    fn id(&self) -> &'static str {
+        "jolt"
+    }
+
+    fn layout(&self) -> &'static WorkspaceLayout {
+        &JOLT_LAYOUT
+    }
+
+    fn guest_header(&self) -> &'static str {
+        JOLT_GUEST_PROGRAM_HEADER
+    }
+
+    fn guest_io(&self) -> Vec<IoRewrite<'static>> {
+        vec![
+            IoRewrite::call(utils::IO_READ, JOLT_IO_READ),
+            IoRewrite::call(utils::IO_COMMIT, JOLT_IO_COMMIT),
+        ]
+    }
+
+    fn prepare_host(&self, program: &Program, root_dir: &Path) -> Result<()> {
+        prepare_host(
+            program,
+            &root_dir.join(JOLT_BASE_HOST),
+            &root_dir.join(JOLT_HOST_MAIN),
+        )
+    }
+
+    fn precompile_patches(&self) -> &'static [PrecompilePatch] {
+        &[]
+    }
+
+    fn pinned_crates(&self) -> &'static [&'static str] {
+        &JOLT_PINNED_CRATES
+    }
+
+    fn prepare_workspace(&self, guest_path: &Path, root_dir: &Path) -> io::Result<()> {
+        let layout = self.layout();
+        utils::prepare_workspace(
+            guest_path,
+            &root_dir.join(layout.guest_dir),
+            &root_dir.join(layout.guest_cargo_toml),
+            &root_dir.join(layout.host_dir),
+            &root_dir.join(layout.host_cargo_toml),
+            &root_dir.join(layout.base_host_cargo_toml),
+            &root_dir.join(layout.base_guest_cargo_toml),
+            &root_dir.join(utils::BASE_FILES_DIR),
+            self.pinned_crates(),
+        )
+    }
+
+    /// Jolt has no GPU prover, `use_gpu` is ignored
+    fn build(
+        &self,
+        workspace_dir: &Path,
+        target_dir: &Path,
+        _use_gpu: bool,
+    ) -> io::Result<ExitStatus> {
+        build_jolt_program(workspace_dir, target_dir)
+    }
+
+    fn prove(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        options: &ProverOptions,
+    ) -> io::Result<ExitStatus> {
+        generate_jolt_proof(host_bin, workspace_dir, output_dir, options)
+    }
+
+    /// Jolt proofs are neither recursively compressed nor wrapped, the host generates a single
+    /// proof of the whole execution
+    fn proof_modes(&self) -> &'static [ProofMode] {
+        &[ProofMode::Compressed]
+    }
+
+    fn supports_mock(&self) -> bool {
+        false
+    }
+
+    /// The guest library is built from `src/main.rs`, so programs cannot have their own
+    fn supports_lib(&self) -> bool {
+        false
+    }
+
+    fn execute(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        input: Option<&ProgramInput>,
+    ) -> io::Result<ExitStatus> {
+        execute_jolt_program(host_bin, workspace_dir, output_dir, input)
+    }
+
+    fn verify(
+        &self,
+        _proof_path: &Path,
+        _program_path: &Path,
+        _pub_input_path: Option<&Path>,
+    ) -> Result<()> {
+        Err(ZkRustError::Verification(
+            "Jolt proofs are only verified by the host when they are generated".to_string(),
+        ))
+    }
+
+    fn artifacts(&self) -> &'static Artifacts {
+        &JOLT_ARTIFACTS
+    }
+
+    fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::Bincode
+    }
+
+    fn export_evm(&self, _artifacts: &ArtifactPaths, _proof_mode: ProofMode) -> Result<EvmExport> {
+        Err(ZkRustError::Export(
+            "Jolt has no EVM verifier contracts".to_string(),
+        ))
+    }
+
+    fn proving_system_id(&self) -> Option<ProvingSystemId> {
+        None
+    }
+
+    fn read_metrics(&self, metrics_path: &Path) -> io::Result<Metrics> {
+        read_metrics(metrics_path).map(Metrics::Jolt)
+    }
+}
@@ -4,7 +4,7 @@ use log::{error, info};
 use serde_json::json;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use aligned_sdk::core::types::{
     AlignedVerificationData, Network, PriceEstimate, ProvingSystemId, VerificationData,
@@ -13,16 +13,47 @@ use aligned_sdk::sdk::{
     deposit_to_aligned, estimate_fee, get_balance_in_aligned, get_chain_id, get_next_nonce,
     submit_and_wait_verification,
 };
-use clap::{Args, ValueEnum};
+use clap::{Args, Subcommand, ValueEnum};
 use dialoguer::Confirm;
 use ethers::prelude::*;
 use ethers::providers::Http;
 use ethers::signers::LocalWallet;
-
+use input::InputFormat;
+use jolt::Jolt;
+#[cfg(feature = "nexus")]
+use nexus::Nexus;
+#[cfg(feature = "openvm")]
+use openvm::OpenVm;
+use risc0::Risc0;
+use sp1::Sp1;
+use zkvm::{ProofMode, ZkVm};
+
+pub mod cache;
+pub mod compare;
+pub mod error;
+pub mod evm;
+pub mod input;
+pub mod jolt;
+pub mod manifest;
+pub mod native;
+#[cfg(feature = "nexus")]
+pub mod nexus;
+#[cfg(feature = "openvm")]
+pub mod openvm;
+pub mod output;
+pub mod pipeline;
+pub mod precompiles;
+pub mod project;
 pub mod risc0;
 pub mod sp1;
 pub mod telemetry;
+pub mod transform;
 pub mod utils;
+pub mod workspace;
+pub mod zkvm;
+
+pub use error::ZkRustError;
+pub use pipeline::{prove, ProveConfig, ProveOutcome};
 
 // Make proof_data path optional
 // Make keystore unneeded
@@ -66,6 +97,29 @@ pub struct ProofArgs {
         env = "ZKRUST_GPU"
     )]
     pub gpu: bool,
+    #[clap(
+        name = "Stage of the generated proof",
+        long = "proof-mode",
+        value_enum,
+        default_value_t = ProofMode::Compressed
+    )]
+    pub proof_mode: ProofMode,
+    #[clap(
+        name = "Generates a mock proof without paying the proving cost",
+        long = "mock"
+    )]
+    pub mock: bool,
+    #[clap(
+        name = "File with the values read by the program, written instead of the ones of input()",
+        long = "input"
+    )]
+    pub input_path: Option<PathBuf>,
+    #[clap(
+        name = "Format of the input file, by default the format of its extension",
+        long = "input-format",
+        value_enum
+    )]
+    pub input_format: Option<InputFormat>,
     #[arg(
         name = "Aligned verification data directory Path",
         long = "aligned-verification-data-path",
@@ -96,6 +150,292 @@ pub struct ProofArgs {
         default_value = "./telemetry"
     )]
     pub telemetry_output_path: String,
+    #[clap(
+        name = "Keep the workspace of the run after it finishes",
+        long = "keep-workspace"
+    )]
+    pub keep_workspace: bool,
+    #[clap(
+        name = "Runs the program natively and checks the public values of the proof match",
+        long = "check-native"
+    )]
+    pub check_native: bool,
+}
+
+impl ProofArgs {
+    /// Library configuration of a proof generation with these arguments.
+    pub fn prove_config(&self, system: SystemArg) -> ProveConfig {
+        ProveConfig {
+            guest_path: PathBuf::from(&self.guest_path),
+            system,
+            precompiles: self.precompiles,
+            gpu: self.gpu,
+            proof_mode: self.proof_mode,
+            mock: self.mock,
+            input: self.input_path.clone(),
+            input_format: self.input_format,
+            proof_data_dir: PathBuf::from(&self.proof_data_directory_path),
+            enable_telemetry: self.enable_telemetry,
+            telemetry_output_dir: Some(PathBuf::from(&self.telemetry_output_path)),
+            keep_workspace: self.keep_workspace,
+            check_native: self.check_native,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ExecuteArgs {
+    pub guest_path: String,
+    #[clap(name = "zkVM to execute the program with", long = "system")]
+    pub system: SystemArg,
+    #[clap(
+        name = "Enables zkVM Acceleration via VM Precompiles",
+        long = "precompiles"
+    )]
+    pub precompiles: bool,
+    #[clap(
+        name = "File with the values read by the program, written instead of the ones of input()",
+        long = "input"
+    )]
+    pub input_path: Option<PathBuf>,
+    #[clap(
+        name = "Format of the input file, by default the format of its extension",
+        long = "input-format",
+        value_enum
+    )]
+    pub input_format: Option<InputFormat>,
+    #[arg(
+        name = "Proof data directory path",
+        long = "proof-data-path",
+        default_value = "./proof_data"
+    )]
+    pub proof_data_directory_path: String,
+    #[clap(
+        name = "Keep the workspace of the run after it finishes",
+        long = "keep-workspace"
+    )]
+    pub keep_workspace: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    #[clap(long = "system")]
+    pub system: SystemArg,
+    #[clap(name = "Path to the proof", long = "proof")]
+    pub proof_path: PathBuf,
+    #[clap(
+        name = "Path to the SP1 program ELF",
+        long = "elf",
+        required_if_eq("system", "sp1"),
+        conflicts_with = "Path to the RISC0 image ID"
+    )]
+    pub elf_path: Option<PathBuf>,
+    #[clap(
+        name = "Path to the RISC0 image ID",
+        long = "image-id",
+        required_if_eq("system", "risc0")
+    )]
+    pub image_id_path: Option<PathBuf>,
+    #[clap(name = "Path to the expected public values", long = "pub")]
+    pub pub_input_path: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportEvmArgs {
+    #[clap(name = "zkVM that generated the proof", long = "system")]
+    pub system: SystemArg,
+    #[arg(
+        name = "Proof data directory path",
+        long = "proof-data-path",
+        default_value = "./proof_data"
+    )]
+    pub proof_data_directory_path: String,
+    #[arg(
+        name = "Directory of the exported Foundry project",
+        long = "out",
+        default_value = "./evm_verifier"
+    )]
+    pub output_path: String,
+}
+
+#[derive(Args, Debug)]
+pub struct DecodeOutputArgs {
+    #[clap(name = "zkVM that generated the proof", long = "system")]
+    pub system: SystemArg,
+    #[arg(
+        name = "Proof data directory path",
+        long = "proof-data-path",
+        default_value = "./proof_data"
+    )]
+    pub proof_data_directory_path: String,
+    #[arg(
+        name = "File the decoded output is written to, printed if not set",
+        long = "out"
+    )]
+    pub output_path: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    #[clap(about = "Remove the cached builds of ~/.zkRust/cache")]
+    Clean {
+        #[clap(name = "Only remove the builds of this zkVM", long = "system")]
+        system: Option<SystemArg>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    pub guest_path: String,
+    #[clap(
+        name = "zkVMs to prove the program with, in order",
+        long = "backends",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "sp1,risc0"
+    )]
+    pub backends: Vec<SystemArg>,
+    #[clap(
+        name = "Stage of the generated proofs, by default compressed or the only stage of a zkVM",
+        long = "proof-mode",
+        value_enum
+    )]
+    pub proof_mode: Option<ProofMode>,
+    #[clap(
+        name = "Enables zkVM Acceleration via VM Precompiles",
+        long = "precompiles"
+    )]
+    pub precompiles: bool,
+    #[clap(
+        name = "Enables GPU acceleration for proof generation",
+        long = "gpu",
+        env = "ZKRUST_GPU"
+    )]
+    pub gpu: bool,
+    #[clap(
+        name = "Generates mock proofs without paying the proving cost",
+        long = "mock"
+    )]
+    pub mock: bool,
+    #[clap(
+        name = "File with the values read by the program, written instead of the ones of input()",
+        long = "input"
+    )]
+    pub input_path: Option<PathBuf>,
+    #[clap(
+        name = "Format of the input file, by default the format of its extension",
+        long = "input-format",
+        value_enum
+    )]
+    pub input_format: Option<InputFormat>,
+    #[arg(
+        name = "Proof data directory path",
+        long = "proof-data-path",
+        default_value = "./proof_data"
+    )]
+    pub proof_data_directory_path: String,
+    #[clap(
+        name = "Path to save telemetry data",
+        long = "telemetry-output",
+        default_value = "./telemetry"
+    )]
+    pub telemetry_output_path: String,
+    #[arg(
+        name = "Directory the comparison report is saved to",
+        long = "out",
+        default_value = "./comparison"
+    )]
+    pub output_path: String,
+    #[clap(
+        name = "Keep the workspaces of the runs after they finish",
+        long = "keep-workspace"
+    )]
+    pub keep_workspace: bool,
+    #[clap(
+        name = "Runs the program natively and checks the public values of each proof match",
+        long = "check-native"
+    )]
+    pub check_native: bool,
+}
+
+impl CompareArgs {
+    /// Library configuration of the proof generation of `system` in a comparison, always
+    /// collecting telemetry. `compare` runs the native check once for all the backends.
+    pub fn prove_config(&self, system: SystemArg, proof_mode: ProofMode) -> ProveConfig {
+        ProveConfig {
+            guest_path: PathBuf::from(&self.guest_path),
+            system,
+            precompiles: self.precompiles,
+            gpu: self.gpu,
+            proof_mode,
+            mock: self.mock,
+            input: self.input_path.clone(),
+            input_format: self.input_format,
+            proof_data_dir: PathBuf::from(&self.proof_data_directory_path),
+            enable_telemetry: true,
+            telemetry_output_dir: Some(PathBuf::from(&self.telemetry_output_path)),
+            keep_workspace: self.keep_workspace,
+            check_native: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum, Copy)]
+pub enum SystemArg {
+    Sp1,
+    Risc0,
+    Jolt,
+    #[cfg(feature = "openvm")]
+    #[value(name = "openvm")]
+    OpenVm,
+    #[cfg(feature = "nexus")]
+    Nexus,
+}
+
+impl SystemArg {
+    pub fn zkvm(self) -> &'static dyn ZkVm {
+        match self {
+            SystemArg::Sp1 => &Sp1,
+            SystemArg::Risc0 => &Risc0,
+            SystemArg::Jolt => &Jolt,
+            #[cfg(feature = "openvm")]
+            SystemArg::OpenVm => &OpenVm,
+            #[cfg(feature = "nexus")]
+            SystemArg::Nexus => &Nexus,
+        }
+    }
+}
+
+/// Backends behind a cargo feature, by feature name, and whether this build includes them.
+pub const OPTIONAL_BACKENDS: [(&str, bool); 2] = [
+    ("openvm", cfg!(feature = "openvm")),
+    ("nexus", cfg!(feature = "nexus")),
+];
+
+/// Prints the backends compiled in with their proof modes, then the optional ones left out.
+pub fn list_backends() {
+    for system in SystemArg::value_variants() {
+        let vm = system.zkvm();
+        let modes = vm
+            .proof_modes()
+            .iter()
+            .map(|mode| mode.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{:<8} {:<8} proof modes: {}", vm.id(), vm.name(), modes);
+    }
+    for (feature, _) in OPTIONAL_BACKENDS.iter().filter(|(_, enabled)| !enabled) {
+        println!(
+            "{:<8} not compiled in, build zkRust with `--features {}`",
+            feature, feature
+        );
+    }
 }
 
 const MIN_FEE_PER_PROOF: u128 = 13_000 * 100_000_000; // gas_price = 0.1 Gwei = 0.0000000001 ether (low gas price)
@@ -118,9 +458,9 @@ impl From<NetworkArg> for Network {
 }
 
 pub async fn submit_proof_to_aligned(
-    proof_path: &str,
-    elf_path: &str,
-    pub_input_path: Option<&str>,
+    proof_path: &Path,
+    elf_path: &Path,
+    pub_input_path: Option<&Path>,
     args: &ProofArgs,
     proof_system_id: ProvingSystemId,
 ) -> Result<(), AlignedError> {
@@ -0,0 +1,341 @@
+use std::{fmt, fs, io, path::Path};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Keys of a dependency which can be merged when two manifests declare the same crate
+const ADDITIVE_DEPENDENCY_KEYS: [&str; 4] = [
+    "features",
+    "default-features",
+    "default_features",
+    "optional",
+];
+
+/// Entry of a program manifest which could not be merged as is into a zkVM manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Dotted path of the entry, e.g. `dependencies.sp1-zkvm`.
+    pub key: String,
+    /// How the conflict was resolved.
+    pub resolution: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.key, self.resolution)
+    }
+}
+
+/// Manifest of a zkVM crate being merged into, keeping its formatting.
+struct Manifest {
+    document: DocumentMut,
+    /// Plain values of `document` as before the merge, compared against the program entries.
+    values: toml::Table,
+    conflicts: Vec<Conflict>,
+}
+
+/// Merges `program`, the resolved manifest of a program, into the zkVM manifest at
+/// `manifest_path`: its dependencies, including the ones of `[target.<cfg>]`, its features and,
+/// if the manifest is a workspace root, its patches and profiles.
+///
+/// Dependencies declared by both are merged with the union of their features. The zkVM keeps its
+/// version of the crates in `pinned`, matched by their package name, otherwise the program's
+/// version is used. Conflicts are returned.
+pub fn merge_program(
+    manifest_path: &Path,
+    program: &toml::Table,
+    pinned: &[&str],
+) -> io::Result<Vec<Conflict>> {
+    let mut manifest = Manifest::read(manifest_path)?;
+
+    if let Some(toml::Value::Table(dependencies)) = program.get("dependencies") {
+        manifest.merge_dependencies(&["dependencies"], dependencies, pinned);
+    }
+    if let Some(toml::Value::Table(targets)) = program.get("target") {
+        for (target, table) in targets {
+            if let Some(toml::Value::Table(dependencies)) = table.get("dependencies") {
+                manifest.merge_dependencies(
+                    &["target", target, "dependencies"],
+                    dependencies,
+                    pinned,
+                );
+            }
+        }
+    }
+    if let Some(toml::Value::Table(features)) = program.get("features") {
+        manifest.merge_features(features);
+    }
+
+    // Cargo only reads patches and profiles from the workspace root
+    for key in ["patch", "profile"] {
+        let Some(toml::Value::Table(table)) = program.get(key) else {
+            continue;
+        };
+        if !manifest.values.contains_key("workspace") {
+            manifest.conflict(key, "ignored, the zkVM crate is not a workspace root");
+        } else if key == "patch" {
+            manifest.merge_patch(table);
+        } else {
+            manifest.merge_profiles(table);
+        }
+    }
+
+    manifest.write(manifest_path)
+}
+
+/// Merges `patch`, the `[patch]` section of a manifest by registry, into the manifest at
+/// `manifest_path`. Crates already patched, e.g. by the program, keep their patch. Conflicts are
+/// returned.
+pub fn merge_patch(manifest_path: &Path, patch: &toml::Table) -> io::Result<Vec<Conflict>> {
+    let mut manifest = Manifest::read(manifest_path)?;
+    manifest.merge_patch(patch);
+    manifest.write(manifest_path)
+}
+
+/// Sets the `[lib]` section of the manifest at `manifest_path` to build `src/lib.rs` as `name`,
+/// keeping the other keys of an existing section.
+pub fn set_lib(manifest_path: &Path, name: &str) -> io::Result<()> {
+    let mut manifest = Manifest::read(manifest_path)?;
+    manifest.insert(&["lib"], "name", &toml::Value::from(name));
+    manifest.insert(&["lib"], "path", &toml::Value::from("src/lib.rs"));
+    manifest.write(manifest_path)?;
+    Ok(())
+}
+
+impl Manifest {
+    fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let invalid = |e: &dyn fmt::Display| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        };
+        Ok(Self {
+            document: contents.parse().map_err(|e| invalid(&e))?,
+            values: toml::from_str(&contents).map_err(|e| invalid(&e))?,
+            conflicts: Vec::new(),
+        })
+    }
+
+    fn write(self, path: &Path) -> io::Result<Vec<Conflict>> {
+        fs::write(path, self.document.to_string())?;
+        Ok(self.conflicts)
+    }
+
+    fn conflict(&mut self, key: impl Into<String>, resolution: impl Into<String>) {
+        self.conflicts.push(Conflict {
+            key: key.into(),
+            resolution: resolution.into(),
+        });
+    }
+
+    /// Plain value at `path` before the merge.
+    fn value(&self, path: &[&str]) -> Option<&toml::Value> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.values.get(*first)?, |value, key| value.get(key))
+    }
+
+    /// Table at `path` of the document, created if missing. `None` if another kind of item is
+    /// there.
+    fn table_mut(&mut self, path: &[&str]) -> Option<&mut Table> {
+        path.iter()
+            .try_fold(self.document.as_table_mut(), |table, key| {
+                let item = table.entry(key).or_insert_with(|| {
+                    let mut table = Table::new();
+                    table.set_implicit(true);
+                    Item::Table(table)
+                });
+                item.as_table_mut()
+            })
+    }
+
+    fn merge_dependencies(&mut self, path: &[&str], dependencies: &toml::Table, pinned: &[&str]) {
+        let existing = self
+            .value(path)
+            .and_then(toml::Value::as_table)
+            .cloned()
+            .unwrap_or_default();
+
+        for (name, dependency) in dependencies {
+            let key = format!("{}.{}", path.join("."), name);
+            let package = package_name(name, dependency);
+
+            if pinned.contains(&package) {
+                let Some((pinned_name, pinned_dependency)) = existing
+                    .iter()
+                    .find(|(name, dependency)| package_name(name, dependency) == package)
+                else {
+                    self.insert(path, name, dependency);
+                    continue;
+                };
+                if pinned_name != name || spec(pinned_dependency) != spec(dependency) {
+                    self.conflict(
+                        key,
+                        format!(
+                            "the version of `{}` pinned by zkRust is used, as `{}`",
+                            package, pinned_name
+                        ),
+                    );
+                    continue;
+                }
+                let merged = union_features(pinned_dependency, dependency);
+                self.insert(path, name, &merged);
+                continue;
+            }
+
+            match existing.get(name) {
+                None => self.insert(path, name, dependency),
+                Some(base) if spec(base) == spec(dependency) => {
+                    let merged = union_features(base, dependency);
+                    self.insert(path, name, &merged);
+                }
+                Some(base) => {
+                    self.conflict(
+                        key,
+                        format!(
+                            "the program's version replaces {} used by the zkVM crate",
+                            base
+                        ),
+                    );
+                    let merged = union_features(dependency, base);
+                    self.insert(path, name, &merged);
+                }
+            }
+        }
+    }
+
+    /// Adds the features of the program, with the union of the `default` features. The zkVM
+    /// keeps its own definition of the features both declare.
+    fn merge_features(&mut self, features: &toml::Table) {
+        let existing = self
+            .value(&["features"])
+            .and_then(toml::Value::as_table)
+            .cloned()
+            .unwrap_or_default();
+
+        for (name, enabled) in features {
+            match existing.get(name) {
+                None => self.insert(&["features"], name, enabled),
+                Some(base) if base == enabled => {}
+                Some(toml::Value::Array(base)) if name == "default" => {
+                    let mut merged = base.clone();
+                    if let toml::Value::Array(enabled) = enabled {
+                        union(&mut merged, enabled);
+                    }
+                    self.insert(&["features"], name, &toml::Value::Array(merged));
+                }
+                Some(_) => self.conflict(
+                    format!("features.{}", name),
+                    "the feature of the zkVM crate is used",
+                ),
+            }
+        }
+    }
+
+    /// Adds the `[patch.<source>]` entries missing from the manifest. Identical patches are
+    /// merged, crates patched differently keep their current patch.
+    fn merge_patch(&mut self, patch: &toml::Table) {
+        for (source, crates) in patch {
+            let toml::Value::Table(crates) = crates else {
+                continue;
+            };
+            for (name, patched) in crates {
+                match self.value(&["patch", source, name]) {
+                    None => self.insert(&["patch", source], name, patched),
+                    Some(existing) if existing == patched => {}
+                    Some(existing) => {
+                        let resolution = format!("the existing patch {} is used", existing);
+                        self.conflict(format!("patch.{}.{}", source, name), resolution);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the profiles of the program, setting the keys the manifest does not.
+    fn merge_profiles(&mut self, profiles: &toml::Table) {
+        for (profile, settings) in profiles {
+            let toml::Value::Table(settings) = settings else {
+                continue;
+            };
+            for (key, value) in settings {
+                match self.value(&["profile", profile, key]) {
+                    None => self.insert(&["profile", profile], key, value),
+                    Some(existing) if existing == value => {}
+                    Some(_) => self.conflict(
+                        format!("profile.{}.{}", profile, key),
+                        "the setting of the zkVM crate is used",
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Sets `key` of the table at `path` to `value`, written inline. Unchanged entries keep
+    /// their formatting.
+    fn insert(&mut self, path: &[&str], key: &str, value: &toml::Value) {
+        let full_path = [path, &[key]].concat();
+        if self.value(&full_path) == Some(value) {
+            return;
+        }
+        let Ok(value) = value.to_string().parse::<toml_edit::Value>() else {
+            return;
+        };
+        let Some(table) = self.table_mut(path) else {
+            let resolution = "not merged, the zkVM manifest holds another kind of value there";
+            self.conflict(full_path.join("."), resolution);
+            return;
+        };
+        let mut value = value.decorated(" ", "");
+        if let Some(existing) = table.get(key).and_then(Item::as_value) {
+            *value.decor_mut() = existing.decor().clone();
+        }
+        table.insert(key, Item::Value(value));
+    }
+}
+
+/// Name of the package a dependency is on, which it is renamed from if it has a `package` key.
+fn package_name<'a>(name: &'a str, dependency: &'a toml::Value) -> &'a str {
+    dependency
+        .get("package")
+        .and_then(toml::Value::as_str)
+        .unwrap_or(name)
+}
+
+/// Source and version of a dependency, without the keys two declarations can differ in.
+fn spec(dependency: &toml::Value) -> toml::Table {
+    match dependency {
+        toml::Value::Table(table) => table
+            .iter()
+            .filter(|(key, _)| !ADDITIVE_DEPENDENCY_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+        version => toml::Table::from_iter([("version".to_string(), version.clone())]),
+    }
+}
+
+/// `base` with the features of `other` added to its own.
+fn union_features(base: &toml::Value, other: &toml::Value) -> toml::Value {
+    let Some(toml::Value::Array(features)) = other.get("features") else {
+        return base.clone();
+    };
+    let mut merged = match base {
+        toml::Value::Table(table) => table.clone(),
+        version => toml::Table::from_iter([("version".to_string(), version.clone())]),
+    };
+    match merged.get_mut("features") {
+        Some(toml::Value::Array(base_features)) => union(base_features, features),
+        _ => {
+            merged.insert("features".to_string(), toml::Value::Array(features.clone()));
+        }
+    }
+    toml::Value::Table(merged)
+}
+
+fn union(values: &mut Vec<toml::Value>, other: &[toml::Value]) {
+    for value in other {
+        if !values.contains(value) {
+            values.push(value.clone());
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use log::info;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
+use syn::parse_quote;
+
+use crate::{
+    error::{Result, ZkRustError},
+    input::ProgramInput,
+    output::OutputEncoding,
+    transform::Program,
+    utils,
+    workspace::RunWorkspace,
+    zkvm::{host_input_args, WorkspaceLayout, ZkVm},
+};
+
+/// Name of the native runs in logs and errors.
+pub const NATIVE_NAME: &str = "native";
+
+/// Native workspace directories. The program is built as a single binary crate, running
+/// `input()`, `main()` and `output()` with in-memory I/O.
+pub const NATIVE_WORKSPACE_ROOT: &str = "workspaces/native";
+pub const NATIVE_BASE_DIR: &str = "workspaces/base_files/native";
+pub const NATIVE_MAIN: &str = "workspaces/native/src/main.rs";
+pub const NATIVE_CARGO_TOML: &str = "workspaces/native/Cargo.toml";
+pub const NATIVE_BASE_CARGO_TOML: &str = "workspaces/base_files/native/cargo_native";
+pub const NATIVE_BASE_MAIN: &str = "workspaces/base_files/native/main";
+
+// Build outputs, relative to the cargo target directory
+pub const NATIVE_BIN: &str = "release/native";
+
+// Public values saved by the native binary, relative to the proof data directory
+pub const NATIVE_PROOF_DATA_DIR: &str = "native";
+pub const NATIVE_BINCODE_PUBLIC_VALUES_PATH: &str = "native_bincode.pub";
+pub const NATIVE_RISC0_PUBLIC_VALUES_PATH: &str = "native_risc0.pub";
+
+pub static NATIVE_LAYOUT: WorkspaceLayout = WorkspaceLayout {
+    root: NATIVE_WORKSPACE_ROOT,
+    base_dir: NATIVE_BASE_DIR,
+    workspace_dir: NATIVE_WORKSPACE_ROOT,
+    guest_dir: NATIVE_WORKSPACE_ROOT,
+    guest_main: NATIVE_MAIN,
+    guest_cargo_toml: NATIVE_CARGO_TOML,
+    host_dir: NATIVE_WORKSPACE_ROOT,
+    host_main: NATIVE_MAIN,
+    host_cargo_toml: NATIVE_CARGO_TOML,
+    base_guest_cargo_toml: NATIVE_BASE_CARGO_TOML,
+    base_host_cargo_toml: NATIVE_BASE_CARGO_TOML,
+    base_host: NATIVE_BASE_MAIN,
+    program_elf: None,
+    host_bin: NATIVE_BIN,
+};
+
+/// `zk_rust_io` crate the native binary is built with, relative to the zkRust home directory.
+/// It is pinned, so the program runs on the in-memory I/O of this version whichever one its
+/// manifest declares.
+pub const NATIVE_IO_CRATE_DIR: &str = "zk_rust_io";
+pub const NATIVE_PINNED_CRATES: [&str; 1] = ["zk_rust_io"];
+
+/// Function `main()` of the program is renamed to, called by the native `main()`
+pub const NATIVE_PROGRAM_MAIN: &str = "__zkrust_main";
+
+/// Public values committed by a native run of a program.
+pub struct NativeOutput {
+    dir: PathBuf,
+}
+
+impl NativeOutput {
+    /// Public values in the encoding of the proofs of `vm`.
+    pub fn public_values(&self, vm: &dyn ZkVm) -> io::Result<Vec<u8>> {
+        let path = match vm.output_encoding() {
+            OutputEncoding::Bincode => NATIVE_BINCODE_PUBLIC_VALUES_PATH,
+            OutputEncoding::Risc0 => NATIVE_RISC0_PUBLIC_VALUES_PATH,
+        };
+        fs::read(self.dir.join(path))
+    }
+
+    /// Fails if the public values saved to `public_input_path` by a proof of `vm` differ from
+    /// the values committed natively.
+    pub fn check(&self, vm: &dyn ZkVm, public_input_path: &Path) -> Result<()> {
+        let expected = self.public_values(vm)?;
+        let public_values = fs::read(public_input_path)?;
+        if public_values == expected {
+            info!("{} public values match the native run", vm.name());
+            return Ok(());
+        }
+        let offset = public_values
+            .iter()
+            .zip(&expected)
+            .position(|(a, b)| a != b)
+            .unwrap_or(public_values.len().min(expected.len()));
+        Err(ZkRustError::OutputMismatch(format!(
+            "{} committed {} bytes and the native run {}, first difference at byte {}",
+            vm.name(),
+            public_values.len(),
+            expected.len(),
+            offset
+        )))
+    }
+}
+
+/// Builds the program in `guest_path` as a native binary and runs it, with the values of `input`
+/// if given, saving the values it commits to `proof_data_dir`.
+pub fn run(
+    guest_path: &Path,
+    input: Option<&ProgramInput>,
+    home_dir: &Path,
+    proof_data_dir: &Path,
+    keep_workspace: bool,
+) -> Result<NativeOutput> {
+    info!("Running natively, program in: {}", guest_path.display());
+    let run = RunWorkspace::create_for(NATIVE_NAME, &NATIVE_LAYOUT, home_dir, keep_workspace)?;
+    let workspace_dir = run.dir().join(NATIVE_WORKSPACE_ROOT);
+    utils::copy_dir_all(
+        &home_dir.join(NATIVE_IO_CRATE_DIR),
+        run.dir().join(NATIVE_IO_CRATE_DIR),
+    )?;
+
+    utils::prepare_crate(
+        guest_path,
+        &workspace_dir,
+        &run.dir().join(NATIVE_CARGO_TOML),
+        &run.dir().join(NATIVE_BASE_CARGO_TOML),
+        Some(&run.dir().join(utils::BASE_FILES_DIR)),
+        &NATIVE_PINNED_CRATES,
+    )?;
+    let program = Program::from_file(&run.dir().join(NATIVE_MAIN))?;
+    prepare_native(
+        &program,
+        &run.dir().join(NATIVE_BASE_MAIN),
+        &run.dir().join(NATIVE_MAIN),
+    )?;
+
+    {
+        let _lock = run.lock_target()?;
+        let build_result = build_native_program(&workspace_dir, run.target_dir())?;
+        if !build_result.success() {
+            return Err(ZkRustError::Build {
+                system: NATIVE_NAME,
+                code: build_result.code(),
+            });
+        }
+        fs::copy(run.target_dir().join(NATIVE_BIN), run.host_bin())?;
+    }
+
+    let output_dir = proof_data_dir.join(NATIVE_PROOF_DATA_DIR);
+    let result = Command::new(run.host_bin())
+        .arg(&output_dir)
+        .args(host_input_args(input))
+        .current_dir(&workspace_dir)
+        .status()?;
+    if !result.success() {
+        return Err(ZkRustError::Execution {
+            system: NATIVE_NAME,
+            code: result.code(),
+        });
+    }
+    info!("Native public values saved to: {}", output_dir.display());
+    Ok(NativeOutput { dir: output_dir })
+}
+
+/// Generates the native binary: the program with its `main()` renamed, followed by the native
+/// template, with `input()` and `output()` filled in. The I/O of every module of the program
+/// runs on `zk_rust_io` as is, which records the commits in the encoding of each zkVM.
+pub fn prepare_native(program: &Program, base_main: &Path, native_main: &Path) -> Result<()> {
+    let mut native_program = program.native_source(NATIVE_PROGRAM_MAIN, &[])?;
+    native_program.push_str(&fs::read_to_string(base_main)?);
+
+    // Insert input body, writing to zk_rust_io as is
+    let input = program.input_body(&[])?;
+    let native_program = native_program.replace(utils::HOST_INPUT, &input);
+    // Insert output body, reading the public values of zk_rust_io as is
+    let output = program.output_body(&[])?;
+    let native_program = native_program.replace(utils::HOST_OUTPUT, &output);
+    // Insert writing of the values given with `--input`
+    let read_inputs = utils::read_inputs_body(
+        program,
+        |ty| parse_quote!(zk_rust_io::write(&inputs.next::<#ty>());),
+    );
+    let native_program = native_program.replace(utils::HOST_READ_INPUTS, &read_inputs);
+
+    let mut file = fs::File::create(native_main)?;
+    file.write_all(native_program.as_bytes())?;
+    Ok(())
+}
+
+/// Build the native binary of the program
+pub fn build_native_program(workspace_dir: &Path, target_dir: &Path) -> io::Result<ExitStatus> {
+    Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .env("CARGO_TARGET_DIR", target_dir)
+        .current_dir(workspace_dir)
+        .status()
+}
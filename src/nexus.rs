@@ -0,0 +1,387 @@
+use aligned_sdk::core::types::ProvingSystemId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+use syn::parse_quote;
+
+use crate::{
+    error::{Result, ZkRustError},
+    evm::EvmExport,
+    input::ProgramInput,
+    output::OutputEncoding,
+    precompiles::PrecompilePatch,
+    telemetry::TelemetryCollector,
+    transform::{IoRewrite, Program},
+    utils,
+    zkvm::{
+        host_input_args, ArtifactPaths, Artifacts, Metrics, ProofMode, ProverOptions, SyscallStats,
+        WorkspaceLayout, ZkVm, HOST_EXECUTE_MODE,
+    },
+};
+
+/// Metrics written by the Nexus host, with the fields of the SP1 metrics. Nexus proofs are not
+/// recursively compressed nor wrapped, the compressed proof is the core proof. Nexus does not
+/// report the cycle count of an execution, `cycles` is unset.
+#[derive(Default, Serialize, Deserialize)]
+pub struct NexusMetrics {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub core_proof_size: usize,
+    pub recursive_proof_size: usize,
+    pub core_prove_duration: Duration,
+    pub core_verify_duration: Duration,
+    pub compress_prove_duration: Duration,
+    pub compress_verify_duration: Duration,
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    #[serde(default)]
+    pub wrap_proof_size: usize,
+    #[serde(default)]
+    pub wrap_prove_duration: Duration,
+    #[serde(default)]
+    pub wrap_verify_duration: Duration,
+    #[serde(default)]
+    pub mock: bool,
+    /// Always empty, Nexus does not report the syscalls of an execution
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    #[serde(default)]
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+impl NexusMetrics {
+    pub fn record(&self, telemetry: &TelemetryCollector) {
+        telemetry.record_zk_metrics(
+            None,
+            Some(self.num_segments),
+            Some(self.core_proof_size),
+            Some(self.recursive_proof_size),
+        );
+        telemetry.record_proof_timings(
+            self.core_prove_duration,
+            self.core_verify_duration,
+            None,
+            None,
+        );
+        telemetry.record_syscalls(&self.syscalls, &self.precompiles);
+    }
+}
+
+/// Nexus workspace directories. The host is the root of the workspace and compiles the guest,
+/// one of its members, when it runs.
+pub const NEXUS_WORKSPACE_ROOT: &str = "workspaces/nexus";
+pub const NEXUS_BASE_DIR: &str = "workspaces/base_files/nexus";
+pub const NEXUS_HOST_DIR: &str = "workspaces/nexus";
+pub const NEXUS_SRC_DIR: &str = "workspaces/nexus/guest";
+pub const NEXUS_GUEST_MAIN: &str = "workspaces/nexus/guest/src/main.rs";
+pub const NEXUS_HOST_MAIN: &str = "workspaces/nexus/src/main.rs";
+pub const NEXUS_BASE_GUEST_CARGO_TOML: &str = "workspaces/base_files/nexus/cargo_guest";
+pub const NEXUS_BASE_HOST_CARGO_TOML: &str = "workspaces/base_files/nexus/cargo_host";
+pub const NEXUS_BASE_HOST: &str = "workspaces/base_files/nexus/host";
+pub const NEXUS_GUEST_CARGO_TOML: &str = "workspaces/nexus/guest/Cargo.toml";
+pub const NEXUS_HOST_CARGO_TOML: &str = "workspaces/nexus/Cargo.toml";
+
+// Build outputs, relative to the cargo target directory
+pub const NEXUS_HOST_BIN: &str = "release/host";
+
+// Proof data generation paths, relative to the proof data directory
+pub const NEXUS_PROOF_DATA_DIR: &str = "nexus";
+pub const NEXUS_ELF_PATH: &str = "nexus.elf";
+pub const NEXUS_PROOF_PATH: &str = "nexus.proof";
+pub const NEXUS_PUB_INPUT_PATH: &str = "nexus.pub";
+pub const NEXUS_METRICS_PATH: &str = "nexus_metrics.json";
+pub const NEXUS_EXECUTION_REPORT_PATH: &str = "nexus_execution.json";
+pub const NEXUS_CALLDATA_PATH: &str = "nexus_calldata.json";
+pub const NEXUS_COMMIT_TYPES_PATH: &str = "nexus_commit_types.json";
+pub const NEXUS_OUTPUT_PATH: &str = "nexus_output.json";
+
+static NEXUS_LAYOUT: WorkspaceLayout = WorkspaceLayout {
+    root: NEXUS_WORKSPACE_ROOT,
+    base_dir: NEXUS_BASE_DIR,
+    workspace_dir: NEXUS_HOST_DIR,
+    guest_dir: NEXUS_SRC_DIR,
+    guest_main: NEXUS_GUEST_MAIN,
+    guest_cargo_toml: NEXUS_GUEST_CARGO_TOML,
+    host_dir: NEXUS_HOST_DIR,
+    host_main: NEXUS_HOST_MAIN,
+    host_cargo_toml: NEXUS_HOST_CARGO_TOML,
+    base_guest_cargo_toml: NEXUS_BASE_GUEST_CARGO_TOML,
+    base_host_cargo_toml: NEXUS_BASE_HOST_CARGO_TOML,
+    base_host: NEXUS_BASE_HOST,
+    program_elf: None,
+    host_bin: NEXUS_HOST_BIN,
+};
+
+static NEXUS_ARTIFACTS: Artifacts = Artifacts {
+    dir: NEXUS_PROOF_DATA_DIR,
+    proof: NEXUS_PROOF_PATH,
+    program: NEXUS_ELF_PATH,
+    public_input: NEXUS_PUB_INPUT_PATH,
+    metrics: NEXUS_METRICS_PATH,
+    execution_report: NEXUS_EXECUTION_REPORT_PATH,
+    calldata: NEXUS_CALLDATA_PATH,
+    commit_types: NEXUS_COMMIT_TYPES_PATH,
+    output: NEXUS_OUTPUT_PATH,
+};
+
+/// Nexus header added to programs for generating proofs of their execution. The guest is built
+/// without `std`, its entrypoint runs `main()` with the private input written by the host and
+/// returns the values it commits as its public output.
+pub const NEXUS_GUEST_PROGRAM_HEADER: &str = r#"
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+
+extern crate alloc;
+
+#[allow(unused_imports)]
+use alloc::{boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+#[allow(unused_imports)]
+use nexus_rt::println;
+
+pub mod zkrust {
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    struct Io {
+        input: RefCell<(Vec<u8>, usize)>,
+        output: RefCell<Vec<u8>>,
+    }
+
+    // The guest runs on a single thread
+    unsafe impl Sync for Io {}
+
+    static IO: Io = Io {
+        input: RefCell::new((Vec::new(), 0)),
+        output: RefCell::new(Vec::new()),
+    };
+
+    #[nexus_rt::main]
+    #[nexus_rt::private_input(input)]
+    fn entry(input: Vec<u8>) -> Vec<u8> {
+        *IO.input.borrow_mut() = (input, 0);
+        super::main();
+        IO.output.take()
+    }
+
+    pub fn read<T: serde::de::DeserializeOwned>() -> T {
+        let mut input = IO.input.borrow_mut();
+        let (bytes, position) = &mut *input;
+        let (value, read) =
+            bincode::serde::decode_from_slice(&bytes[*position..], bincode::config::legacy())
+                .expect("Failed to read input");
+        *position += read;
+        value
+    }
+
+    pub fn commit<T: serde::Serialize>(value: &T) {
+        let bytes = bincode::serde::encode_to_vec(value, bincode::config::legacy())
+            .expect("Failed to commit");
+        IO.output.borrow_mut().extend(bytes);
+    }
+}
+"#;
+
+/// Nexus crates the program manifest cannot override in the guest and host manifests
+pub const NEXUS_PINNED_CRATES: [&str; 2] = ["nexus-rt", "nexus-sdk"];
+
+/// Nexus User I/O
+// Host
+pub const NEXUS_HOST_WRITE: &str = "stdin.write";
+pub const NEXUS_HOST_READ: &str = "public_values.read";
+
+// Guest
+pub const NEXUS_IO_READ: &str = "crate::zkrust::read";
+pub const NEXUS_IO_COMMIT: &str = "crate::zkrust::commit";
+
+pub fn prepare_host(program: &Program, host_dir: &PathBuf, host_main: &PathBuf) -> Result<()> {
+    let mut host_program = program.host_items();
+    let contents = fs::read_to_string(host_dir)?;
+
+    host_program.push_str(&contents);
+
+    // Insert input body, replacing zkRust::write
+    let input = program.input_body(&[IoRewrite::call(utils::IO_WRITE, NEXUS_HOST_WRITE)])?;
+    let host_program = host_program.replace(utils::HOST_INPUT, &input);
+    // Insert output body, replacing zkRust::out()
+    let output = program.output_body(&[IoRewrite::call(utils::IO_OUT, NEXUS_HOST_READ)])?;
+    let host_program = host_program.replace(utils::HOST_OUTPUT, &output);
+    // Insert writing of the values given with `--input`
+    let read_inputs = utils::read_inputs_body(
+        program,
+        |ty| parse_quote!(stdin.write(&inputs.next::<#ty>());),
+    );
+    let host_program = host_program.replace(utils::HOST_READ_INPUTS, &read_inputs);
+
+    // Write to host
+    let mut file = fs::File::create(host_main)?;
+    file.write_all(host_program.as_bytes())?;
+    Ok(())
+}
+
+/// Build the Nexus host, which compiles the guest when it runs
+pub fn build_nexus_program(workspace_dir: &Path, target_dir: &Path) -> io::Result<ExitStatus> {
+    Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .arg("--bin")
+        .arg("host")
+        .env("CARGO_TARGET_DIR", target_dir)
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Generates a Nexus proof and ELF, saving them to `output_dir`
+pub fn generate_nexus_proof(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    options: &ProverOptions,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(options.proof_mode.as_str())
+        .args(host_input_args(options.input.as_ref()))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Runs the program in the Nexus VM without generating a proof
+pub fn execute_nexus_program(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    input: Option<&ProgramInput>,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(HOST_EXECUTE_MODE)
+        .args(host_input_args(input))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+pub fn read_metrics(metrics_path: &Path) -> io::Result<NexusMetrics> {
+    let metrics_str = fs::read_to_string(metrics_path)?;
+    serde_json::from_str(&metrics_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub struct Nexus;
+
+impl ZkVm for Nexus {
+    fn name(&self) -> &'static str {
+        "Nexus"
+    }
+
+    fn id(&self) -> &'static str {
+        "nexus"
+    }
+
+    fn layout(&self) -> &'static WorkspaceLayout {
+        &NEXUS_LAYOUT
+    }
+
+    fn guest_header(&self) -> &'static str {
+        NEXUS_GUEST_PROGRAM_HEADER
+    }
+
+    fn guest_io(&self) -> Vec<IoRewrite<'static>> {
+        vec![
+            IoRewrite::call(utils::IO_READ, NEXUS_IO_READ),
+            IoRewrite::call(utils::IO_COMMIT, NEXUS_IO_COMMIT),
+        ]
+    }
+
+    fn prepare_host(&self, program: &Program, root_dir: &Path) -> Result<()> {
+        prepare_host(
+            program,
+            &root_dir.join(NEXUS_BASE_HOST),
+            &root_dir.join(NEXUS_HOST_MAIN),
+        )
+    }
+
+    fn precompile_patches(&self) -> &'static [PrecompilePatch] {
+        &[]
+    }
+
+    fn pinned_crates(&self) -> &'static [&'static str] {
+        &NEXUS_PINNED_CRATES
+    }
+
+    /// Nexus has no GPU prover, `use_gpu` is ignored
+    fn build(
+        &self,
+        workspace_dir: &Path,
+        target_dir: &Path,
+        _use_gpu: bool,
+    ) -> io::Result<ExitStatus> {
+        build_nexus_program(workspace_dir, target_dir)
+    }
+
+    fn prove(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        options: &ProverOptions,
+    ) -> io::Result<ExitStatus> {
+        generate_nexus_proof(host_bin, workspace_dir, output_dir, options)
+    }
+
+    /// Nexus proofs are neither recursively compressed nor wrapped, the host generates a single
+    /// proof of the whole execution
+    fn proof_modes(&self) -> &'static [ProofMode] {
+        &[ProofMode::Compressed]
+    }
+
+    fn supports_mock(&self) -> bool {
+        false
+    }
+
+    fn execute(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        input: Option<&ProgramInput>,
+    ) -> io::Result<ExitStatus> {
+        execute_nexus_program(host_bin, workspace_dir, output_dir, input)
+    }
+
+    fn verify(
+        &self,
+        _proof_path: &Path,
+        _program_path: &Path,
+        _pub_input_path: Option<&Path>,
+    ) -> Result<()> {
+        Err(ZkRustError::Verification(
+            "Nexus proofs are only verified by the host when they are generated".to_string(),
+        ))
+    }
+
+    fn artifacts(&self) -> &'static Artifacts {
+        &NEXUS_ARTIFACTS
+    }
+
+    fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::Bincode
+    }
+
+    fn export_evm(&self, _artifacts: &ArtifactPaths, _proof_mode: ProofMode) -> Result<EvmExport> {
+        Err(ZkRustError::Export(
+            "Nexus has no EVM verifier contracts".to_string(),
+        ))
+    }
+
+    fn proving_system_id(&self) -> Option<ProvingSystemId> {
+        None
+    }
+
+    fn read_metrics(&self, metrics_path: &Path) -> io::Result<Metrics> {
+        read_metrics(metrics_path).map(Metrics::Nexus)
+    }
+}
@@ -0,0 +1,358 @@
+use aligned_sdk::core::types::ProvingSystemId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+use syn::parse_quote;
+
+use crate::{
+    error::{Result, ZkRustError},
+    evm::EvmExport,
+    input::ProgramInput,
+    output::OutputEncoding,
+    precompiles::PrecompilePatch,
+    telemetry::TelemetryCollector,
+    transform::{IoRewrite, Program},
+    utils,
+    zkvm::{
+        host_input_args, ArtifactPaths, Artifacts, Metrics, ProofMode, ProverOptions, SyscallStats,
+        WorkspaceLayout, ZkVm, HOST_EXECUTE_MODE,
+    },
+};
+
+/// Metrics written by the OpenVM host, with the fields of the SP1 metrics. The host only proves
+/// the segments of the execution, the compression and wrapping fields are unset.
+#[derive(Default, Serialize, Deserialize)]
+pub struct OpenVmMetrics {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub core_proof_size: usize,
+    pub recursive_proof_size: usize,
+    pub core_prove_duration: Duration,
+    pub core_verify_duration: Duration,
+    pub compress_prove_duration: Duration,
+    pub compress_verify_duration: Duration,
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    #[serde(default)]
+    pub wrap_proof_size: usize,
+    #[serde(default)]
+    pub wrap_prove_duration: Duration,
+    #[serde(default)]
+    pub wrap_verify_duration: Duration,
+    #[serde(default)]
+    pub mock: bool,
+    /// Always empty, OpenVM does not report the syscalls of an execution
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    #[serde(default)]
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+impl OpenVmMetrics {
+    pub fn record(&self, telemetry: &TelemetryCollector) {
+        telemetry.record_zk_metrics(
+            Some(self.cycles),
+            Some(self.num_segments),
+            Some(self.core_proof_size),
+            None,
+        );
+        telemetry.record_proof_timings(
+            self.core_prove_duration,
+            self.core_verify_duration,
+            None,
+            None,
+        );
+        telemetry.record_syscalls(&self.syscalls, &self.precompiles);
+    }
+}
+
+/// OpenVM workspace directories. The host is the root of the workspace and compiles the guest,
+/// one of its members, when it runs.
+pub const OPENVM_WORKSPACE_ROOT: &str = "workspaces/openvm";
+pub const OPENVM_BASE_DIR: &str = "workspaces/base_files/openvm";
+pub const OPENVM_HOST_DIR: &str = "workspaces/openvm";
+pub const OPENVM_SRC_DIR: &str = "workspaces/openvm/guest";
+pub const OPENVM_GUEST_MAIN: &str = "workspaces/openvm/guest/src/main.rs";
+pub const OPENVM_HOST_MAIN: &str = "workspaces/openvm/src/main.rs";
+pub const OPENVM_BASE_GUEST_CARGO_TOML: &str = "workspaces/base_files/openvm/cargo_guest";
+pub const OPENVM_BASE_HOST_CARGO_TOML: &str = "workspaces/base_files/openvm/cargo_host";
+pub const OPENVM_BASE_HOST: &str = "workspaces/base_files/openvm/host";
+pub const OPENVM_GUEST_CARGO_TOML: &str = "workspaces/openvm/guest/Cargo.toml";
+pub const OPENVM_HOST_CARGO_TOML: &str = "workspaces/openvm/Cargo.toml";
+
+// Build outputs, relative to the cargo target directory
+pub const OPENVM_HOST_BIN: &str = "release/host";
+
+// Proof data generation paths, relative to the proof data directory
+pub const OPENVM_PROOF_DATA_DIR: &str = "openvm";
+pub const OPENVM_ELF_PATH: &str = "openvm.elf";
+pub const OPENVM_PROOF_PATH: &str = "openvm.proof";
+pub const OPENVM_PUB_INPUT_PATH: &str = "openvm.pub";
+pub const OPENVM_METRICS_PATH: &str = "openvm_metrics.json";
+pub const OPENVM_EXECUTION_REPORT_PATH: &str = "openvm_execution.json";
+pub const OPENVM_CALLDATA_PATH: &str = "openvm_calldata.json";
+pub const OPENVM_COMMIT_TYPES_PATH: &str = "openvm_commit_types.json";
+pub const OPENVM_OUTPUT_PATH: &str = "openvm_output.json";
+
+static OPENVM_LAYOUT: WorkspaceLayout = WorkspaceLayout {
+    root: OPENVM_WORKSPACE_ROOT,
+    base_dir: OPENVM_BASE_DIR,
+    workspace_dir: OPENVM_HOST_DIR,
+    guest_dir: OPENVM_SRC_DIR,
+    guest_main: OPENVM_GUEST_MAIN,
+    guest_cargo_toml: OPENVM_GUEST_CARGO_TOML,
+    host_dir: OPENVM_HOST_DIR,
+    host_main: OPENVM_HOST_MAIN,
+    host_cargo_toml: OPENVM_HOST_CARGO_TOML,
+    base_guest_cargo_toml: OPENVM_BASE_GUEST_CARGO_TOML,
+    base_host_cargo_toml: OPENVM_BASE_HOST_CARGO_TOML,
+    base_host: OPENVM_BASE_HOST,
+    program_elf: None,
+    host_bin: OPENVM_HOST_BIN,
+};
+
+static OPENVM_ARTIFACTS: Artifacts = Artifacts {
+    dir: OPENVM_PROOF_DATA_DIR,
+    proof: OPENVM_PROOF_PATH,
+    program: OPENVM_ELF_PATH,
+    public_input: OPENVM_PUB_INPUT_PATH,
+    metrics: OPENVM_METRICS_PATH,
+    execution_report: OPENVM_EXECUTION_REPORT_PATH,
+    calldata: OPENVM_CALLDATA_PATH,
+    commit_types: OPENVM_COMMIT_TYPES_PATH,
+    output: OPENVM_OUTPUT_PATH,
+};
+
+/// OpenVM header added to programs for generating proofs of their execution. Committed values
+/// are revealed as words of the public values, after the count of words revealed so far, as the
+/// public values of OpenVM have a fixed size.
+pub const OPENVM_GUEST_PROGRAM_HEADER: &str = r#"
+openvm::entry!(main);
+
+pub mod zkrust {
+    use core::cell::Cell;
+
+    struct Revealed(Cell<usize>);
+
+    // The guest runs on a single thread
+    unsafe impl Sync for Revealed {}
+
+    static REVEALED: Revealed = Revealed(Cell::new(0));
+
+    pub fn commit<T: serde::Serialize>(value: &T) {
+        let words = openvm::serde::to_vec(value).expect("Failed to commit");
+        let start = REVEALED.0.get();
+        for (i, word) in words.iter().enumerate() {
+            openvm::io::reveal_u32(*word, start + i + 1);
+        }
+        REVEALED.0.set(start + words.len());
+        openvm::io::reveal_u32(REVEALED.0.get() as u32, 0);
+    }
+}
+"#;
+
+/// OpenVM crates the program manifest cannot override in the guest and host manifests
+pub const OPENVM_PINNED_CRATES: [&str; 2] = ["openvm", "openvm-sdk"];
+
+/// OpenVM User I/O
+// Host
+pub const OPENVM_HOST_WRITE: &str = "stdin.write";
+pub const OPENVM_HOST_READ: &str = "journal.read";
+
+// Guest
+pub const OPENVM_IO_READ: &str = "openvm::io::read";
+pub const OPENVM_IO_COMMIT: &str = "crate::zkrust::commit";
+
+pub fn prepare_host(program: &Program, host_dir: &PathBuf, host_main: &PathBuf) -> Result<()> {
+    let mut host_program = program.host_items();
+    let contents = fs::read_to_string(host_dir)?;
+
+    host_program.push_str(&contents);
+
+    // Insert input body, replacing zkRust::write
+    let input = program.input_body(&[IoRewrite::call(utils::IO_WRITE, OPENVM_HOST_WRITE)])?;
+    let host_program = host_program.replace(utils::HOST_INPUT, &input);
+    // Insert output body, replacing zkRust::out()
+    let output = program.output_body(&[IoRewrite::call(utils::IO_OUT, OPENVM_HOST_READ)])?;
+    let host_program = host_program.replace(utils::HOST_OUTPUT, &output);
+    // Insert writing of the values given with `--input`
+    let read_inputs = utils::read_inputs_body(
+        program,
+        |ty| parse_quote!(stdin.write(&inputs.next::<#ty>());),
+    );
+    let host_program = host_program.replace(utils::HOST_READ_INPUTS, &read_inputs);
+
+    // Write to host
+    let mut file = fs::File::create(host_main)?;
+    file.write_all(host_program.as_bytes())?;
+    Ok(())
+}
+
+/// Build the OpenVM host, which compiles the guest when it runs
+pub fn build_openvm_program(workspace_dir: &Path, target_dir: &Path) -> io::Result<ExitStatus> {
+    Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .arg("--bin")
+        .arg("host")
+        .env("CARGO_TARGET_DIR", target_dir)
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Generates an OpenVM proof and ELF, saving them to `output_dir`
+pub fn generate_openvm_proof(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    options: &ProverOptions,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(options.proof_mode.as_str())
+        .args(host_input_args(options.input.as_ref()))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Runs the OpenVM executor on the program without generating a proof
+pub fn execute_openvm_program(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    input: Option<&ProgramInput>,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(HOST_EXECUTE_MODE)
+        .args(host_input_args(input))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+pub fn read_metrics(metrics_path: &Path) -> io::Result<OpenVmMetrics> {
+    let metrics_str = fs::read_to_string(metrics_path)?;
+    serde_json::from_str(&metrics_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub struct OpenVm;
+
+impl ZkVm for OpenVm {
+    fn name(&self) -> &'static str {
+        "OpenVM"
+    }
+
+    fn id(&self) -> &'static str {
+        "openvm"
+    }
+
+    fn layout(&self) -> &'static WorkspaceLayout {
+        &OPENVM_LAYOUT
+    }
+
+    fn guest_header(&self) -> &'static str {
+        OPENVM_GUEST_PROGRAM_HEADER
+    }
+
+    fn guest_io(&self) -> Vec<IoRewrite<'static>> {
+        vec![
+            IoRewrite::call(utils::IO_READ, OPENVM_IO_READ),
+            IoRewrite::call(utils::IO_COMMIT, OPENVM_IO_COMMIT),
+        ]
+    }
+
+    fn prepare_host(&self, program: &Program, root_dir: &Path) -> Result<()> {
+        prepare_host(
+            program,
+            &root_dir.join(OPENVM_BASE_HOST),
+            &root_dir.join(OPENVM_HOST_MAIN),
+        )
+    }
+
+    fn precompile_patches(&self) -> &'static [PrecompilePatch] {
+        &[]
+    }
+
+    fn pinned_crates(&self) -> &'static [&'static str] {
+        &OPENVM_PINNED_CRATES
+    }
+
+    /// The host does not use the GPU prover of OpenVM, `use_gpu` is ignored
+    fn build(
+        &self,
+        workspace_dir: &Path,
+        target_dir: &Path,
+        _use_gpu: bool,
+    ) -> io::Result<ExitStatus> {
+        build_openvm_program(workspace_dir, target_dir)
+    }
+
+    fn prove(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        options: &ProverOptions,
+    ) -> io::Result<ExitStatus> {
+        generate_openvm_proof(host_bin, workspace_dir, output_dir, options)
+    }
+
+    /// The host only proves the segments of the execution, without aggregating them
+    fn proof_modes(&self) -> &'static [ProofMode] {
+        &[ProofMode::Core]
+    }
+
+    fn supports_mock(&self) -> bool {
+        false
+    }
+
+    fn execute(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        input: Option<&ProgramInput>,
+    ) -> io::Result<ExitStatus> {
+        execute_openvm_program(host_bin, workspace_dir, output_dir, input)
+    }
+
+    fn verify(
+        &self,
+        _proof_path: &Path,
+        _program_path: &Path,
+        _pub_input_path: Option<&Path>,
+    ) -> Result<()> {
+        Err(ZkRustError::Verification(
+            "OpenVM proofs are only verified by the host when they are generated".to_string(),
+        ))
+    }
+
+    fn artifacts(&self) -> &'static Artifacts {
+        &OPENVM_ARTIFACTS
+    }
+
+    /// OpenVM serde pads values to words like RISC0 serde
+    fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::Risc0
+    }
+
+    fn export_evm(&self, _artifacts: &ArtifactPaths, _proof_mode: ProofMode) -> Result<EvmExport> {
+        Err(ZkRustError::Export(
+            "zkRust does not export OpenVM proofs to the EVM".to_string(),
+        ))
+    }
+
+    fn proving_system_id(&self) -> Option<ProvingSystemId> {
+        None
+    }
+
+    fn read_metrics(&self, metrics_path: &Path) -> io::Result<Metrics> {
+        read_metrics(metrics_path).map(Metrics::OpenVm)
+    }
+}
@@ -0,0 +1,395 @@
+use log::info;
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{fs, path::Path};
+use syn::{Expr, Fields, GenericArgument, Item, Lit, PathArguments, Type};
+
+use crate::{
+    error::{Result, ZkRustError},
+    transform::Program,
+    utils,
+    zkvm::{ArtifactPaths, ZkVm},
+    DecodeOutputArgs,
+};
+
+/// Serialization of the values committed by the guest in the public values of a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// `bincode` with fixed-size integers and `u64` lengths, as written by SP1.
+    Bincode,
+    /// RISC0 serde, where every value is padded to 32-bit words and lengths are `u32`.
+    Risc0,
+}
+
+/// Layout of a committed value, as needed to decode it from the public values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    Unit,
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
+    Char,
+    String,
+    Option(Box<ValueType>),
+    Vec(Box<ValueType>),
+    Array(Box<ValueType>, usize),
+    Tuple(Vec<ValueType>),
+    Struct(Vec<(String, ValueType)>),
+}
+
+/// Type of a value committed by `main()`, recorded when the program is transformed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitType {
+    /// Type as written in the program, e.g. `Vec<u32>`.
+    #[serde(rename = "type")]
+    pub name: String,
+    pub layout: ValueType,
+}
+
+/// Types of the values committed by `main()` of `program`, in order.
+pub fn commit_types(program: &Program) -> Result<Vec<CommitType>> {
+    program
+        .commit_types(utils::IO_COMMIT, utils::IO_OUT)?
+        .iter()
+        .map(|ty| {
+            Ok(CommitType {
+                name: type_name(ty),
+                layout: value_type(program, ty)?,
+            })
+        })
+        .collect()
+}
+
+/// Decodes `public_values` into a JSON array with one `{"type", "value"}` entry per commit.
+pub fn decode(
+    types: &[CommitType],
+    public_values: &[u8],
+    encoding: OutputEncoding,
+) -> Result<Value> {
+    let mut decoder = Decoder {
+        bytes: public_values,
+        encoding,
+    };
+    let entries = types
+        .iter()
+        .map(|ty| {
+            Ok(json!({
+                "type": ty.name,
+                "value": decoder.value(&ty.layout)?,
+            }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if !decoder.bytes.is_empty() {
+        return Err(ZkRustError::Output(format!(
+            "{} bytes of the public values are left after decoding the committed types",
+            decoder.bytes.len()
+        )));
+    }
+    Ok(Value::Array(entries))
+}
+
+/// Records the commit types of `program` next to the proof in `artifacts`.
+pub fn save_commit_types(program: &Program, artifacts: &ArtifactPaths) -> Result<()> {
+    let types = commit_types(program)?;
+    fs::create_dir_all(&artifacts.dir)?;
+    let types = serde_json::to_string_pretty(&types).map_err(std::io::Error::from)?;
+    fs::write(&artifacts.commit_types, types)?;
+    Ok(())
+}
+
+/// Decodes the public values saved in `artifacts` with their recorded commit types.
+pub fn decode_saved(vm: &dyn ZkVm, artifacts: &ArtifactPaths) -> Result<Value> {
+    let types = fs::read_to_string(&artifacts.commit_types).map_err(|e| {
+        ZkRustError::Output(format!(
+            "no commit types recorded in {} ({}), prove the program again",
+            artifacts.commit_types.display(),
+            e
+        ))
+    })?;
+    let types: Vec<CommitType> =
+        serde_json::from_str(&types).map_err(|e| ZkRustError::Output(e.to_string()))?;
+    let public_values = fs::read(&artifacts.public_input)?;
+    decode(&types, &public_values, vm.output_encoding())
+}
+
+/// Decodes the public values of the proof saved in the proof data directory of `vm`, writing
+/// the JSON document to `args.output_path` or printing it.
+pub fn decode_output(vm: &dyn ZkVm, args: &DecodeOutputArgs) -> Result<()> {
+    let artifacts = vm
+        .artifacts()
+        .paths(Path::new(&args.proof_data_directory_path));
+    let output = decode_saved(vm, &artifacts)?;
+    let output = serde_json::to_string_pretty(&output).map_err(std::io::Error::from)?;
+    match &args.output_path {
+        Some(path) => {
+            fs::write(path, output)?;
+            info!("{} output saved to: {}", vm.name(), path.display());
+        }
+        None => println!("{}", output),
+    }
+    Ok(())
+}
+
+fn type_name(ty: &Type) -> String {
+    ty.to_token_stream()
+        .to_string()
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace(" ;", ";")
+        .replace("& ", "&")
+}
+
+fn value_type(program: &Program, ty: &Type) -> Result<ValueType> {
+    let unsupported = || {
+        ZkRustError::Output(format!(
+            "committed type `{}` cannot be decoded",
+            type_name(ty)
+        ))
+    };
+    match ty {
+        Type::Paren(paren) => value_type(program, &paren.elem),
+        Type::Group(group) => value_type(program, &group.elem),
+        Type::Reference(reference) => value_type(program, &reference.elem),
+        Type::Slice(slice) => Ok(ValueType::Vec(Box::new(value_type(program, &slice.elem)?))),
+        Type::Array(array) => {
+            let Expr::Lit(len) = &array.len else {
+                return Err(unsupported());
+            };
+            let Lit::Int(len) = &len.lit else {
+                return Err(unsupported());
+            };
+            let len = len.base10_parse().map_err(|_| unsupported())?;
+            Ok(ValueType::Array(
+                Box::new(value_type(program, &array.elem)?),
+                len,
+            ))
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Ok(ValueType::Unit),
+        Type::Tuple(tuple) => Ok(ValueType::Tuple(
+            tuple
+                .elems
+                .iter()
+                .map(|elem| value_type(program, elem))
+                .collect::<Result<_>>()?,
+        )),
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last().ok_or_else(unsupported)?;
+            let argument = || match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                }),
+                _ => None,
+            };
+            let layout = match segment.ident.to_string().as_str() {
+                "bool" => ValueType::Bool,
+                "u8" => ValueType::U8,
+                "u16" => ValueType::U16,
+                "u32" => ValueType::U32,
+                // Serialized as 64-bit integers
+                "u64" | "usize" => ValueType::U64,
+                "u128" => ValueType::U128,
+                "i8" => ValueType::I8,
+                "i16" => ValueType::I16,
+                "i32" => ValueType::I32,
+                "i64" | "isize" => ValueType::I64,
+                "i128" => ValueType::I128,
+                "f32" => ValueType::F32,
+                "f64" => ValueType::F64,
+                "char" => ValueType::Char,
+                "String" | "str" => ValueType::String,
+                "Box" => value_type(program, argument().ok_or_else(unsupported)?)?,
+                "Option" => ValueType::Option(Box::new(value_type(
+                    program,
+                    argument().ok_or_else(unsupported)?,
+                )?)),
+                "Vec" => ValueType::Vec(Box::new(value_type(
+                    program,
+                    argument().ok_or_else(unsupported)?,
+                )?)),
+                name => struct_type(program, name).ok_or_else(unsupported)??,
+            };
+            Ok(layout)
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+/// Layout of the non-generic struct `name` declared in `program`.
+fn struct_type(program: &Program, name: &str) -> Option<Result<ValueType>> {
+    let item = program.items().find_map(|item| match item {
+        Item::Struct(item) if item.ident == name && item.generics.params.is_empty() => Some(item),
+        _ => None,
+    })?;
+    let layout = match &item.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| {
+                let name = field.ident.as_ref().map(ToString::to_string);
+                Ok((name.unwrap_or_default(), value_type(program, &field.ty)?))
+            })
+            .collect::<Result<_>>()
+            .map(ValueType::Struct),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .map(|field| value_type(program, &field.ty))
+            .collect::<Result<_>>()
+            .map(ValueType::Tuple),
+        Fields::Unit => Ok(ValueType::Unit),
+    };
+    Some(layout)
+}
+
+/// Reads values from the front of the public values.
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    encoding: OutputEncoding,
+}
+
+impl Decoder<'_> {
+    fn value(&mut self, layout: &ValueType) -> Result<Value> {
+        let value = match layout {
+            ValueType::Unit => Value::Null,
+            ValueType::Bool => Value::Bool(self.uint(1)? != 0),
+            ValueType::U8 => json!(self.uint(1)? as u8),
+            ValueType::U16 => json!(self.uint(2)? as u16),
+            ValueType::U32 => json!(self.uint(4)? as u32),
+            ValueType::U64 => json!(self.uint(8)? as u64),
+            // Outside the range of JSON numbers supported by most parsers
+            ValueType::U128 => json!(self.uint(16)?.to_string()),
+            ValueType::I8 => json!(self.int(1)? as i8),
+            ValueType::I16 => json!(self.int(2)? as i16),
+            ValueType::I32 => json!(self.int(4)? as i32),
+            ValueType::I64 => json!(self.int(8)? as i64),
+            ValueType::I128 => json!(self.int(16)?.to_string()),
+            ValueType::F32 => json!(f32::from_bits(self.uint(4)? as u32)),
+            ValueType::F64 => json!(f64::from_bits(self.uint(8)? as u64)),
+            ValueType::Char => json!(self.char()?.to_string()),
+            ValueType::String => {
+                let len = self.len()?;
+                let bytes = self.padded(len)?;
+                let string = std::str::from_utf8(bytes)
+                    .map_err(|e| ZkRustError::Output(format!("invalid string: {}", e)))?;
+                json!(string)
+            }
+            ValueType::Option(layout) => match self.uint(1)? {
+                0 => Value::Null,
+                _ => self.value(layout)?,
+            },
+            ValueType::Vec(layout) => {
+                let len = self.len()?;
+                Value::Array(
+                    (0..len)
+                        .map(|_| self.value(layout))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            ValueType::Array(layout, len) => Value::Array(
+                (0..*len)
+                    .map(|_| self.value(layout))
+                    .collect::<Result<_>>()?,
+            ),
+            ValueType::Tuple(layouts) => Value::Array(
+                layouts
+                    .iter()
+                    .map(|layout| self.value(layout))
+                    .collect::<Result<_>>()?,
+            ),
+            ValueType::Struct(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, layout)| Ok((name.clone(), self.value(layout)?)))
+                    .collect::<Result<_>>()?,
+            ),
+        };
+        Ok(value)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        if self.bytes.len() < len {
+            return Err(ZkRustError::Output(
+                "public values are shorter than the committed types".to_string(),
+            ));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Takes `len` bytes, followed by their padding to a word for RISC0.
+    fn padded(&mut self, len: usize) -> Result<&[u8]> {
+        let padded_len = match self.encoding {
+            OutputEncoding::Bincode => len,
+            OutputEncoding::Risc0 => len.div_ceil(4) * 4,
+        };
+        Ok(&self.take(padded_len)?[..len])
+    }
+
+    /// Little-endian integer of `size` bytes, held in a whole word for RISC0.
+    fn int_bytes(&mut self, size: usize) -> Result<&[u8]> {
+        match self.encoding {
+            OutputEncoding::Risc0 => self.take(size.max(4)),
+            OutputEncoding::Bincode => self.take(size),
+        }
+    }
+
+    fn uint(&mut self, size: usize) -> Result<u128> {
+        let bytes = self.int_bytes(size)?;
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0, |value, byte| (value << 8) | u128::from(*byte)))
+    }
+
+    fn int(&mut self, size: usize) -> Result<i128> {
+        let bytes = self.int_bytes(size)?;
+        let negative = bytes.last().is_some_and(|byte| byte & 0x80 != 0);
+        let mut extended = [if negative { 0xff } else { 0 }; 16];
+        extended[..bytes.len()].copy_from_slice(bytes);
+        Ok(i128::from_le_bytes(extended))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        let len = match self.encoding {
+            OutputEncoding::Bincode => self.uint(8)?,
+            OutputEncoding::Risc0 => self.uint(4)?,
+        };
+        usize::try_from(len).map_err(|_| ZkRustError::Output(format!("invalid length {}", len)))
+    }
+
+    fn char(&mut self) -> Result<char> {
+        let invalid = || ZkRustError::Output("invalid char".to_string());
+        match self.encoding {
+            OutputEncoding::Risc0 => char::from_u32(self.uint(4)? as u32).ok_or_else(invalid),
+            // UTF-8 encoded, with the length given by the leading byte
+            OutputEncoding::Bincode => {
+                let len = match self.bytes.first().ok_or_else(invalid)? {
+                    byte if byte & 0x80 == 0 => 1,
+                    byte if byte & 0xe0 == 0xc0 => 2,
+                    byte if byte & 0xf0 == 0xe0 => 3,
+                    _ => 4,
+                };
+                std::str::from_utf8(self.take(len)?)
+                    .ok()
+                    .and_then(|s| s.chars().next())
+                    .ok_or_else(invalid)
+            }
+        }
+    }
+}
@@ -0,0 +1,597 @@
+use log::{error, info, warn};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::{
+    cache::{self, BuildCache},
+    error::{Result, ZkRustError},
+    input::{InputFormat, ProgramInput},
+    native, output,
+    precompiles::{self, AppliedPrecompile},
+    submit_proof_to_aligned,
+    telemetry::{TelemetryCollector, TelemetryData},
+    transform::Program,
+    utils,
+    workspace::RunWorkspace,
+    zkvm::{ArtifactPaths, Metrics, ProofMode, ProverOptions, ZkVm},
+    ExecuteArgs, ProofArgs, SystemArg, VerifyArgs,
+};
+
+/// Options of a proof generation through the library API.
+pub struct ProveConfig {
+    /// Directory of the program, with the structure described in the README.
+    pub guest_path: PathBuf,
+    pub system: SystemArg,
+    pub precompiles: bool,
+    pub gpu: bool,
+    pub proof_mode: ProofMode,
+    /// Generates a mock proof without paying the proving cost, for testing programs.
+    pub mock: bool,
+    /// File holding the values read by the guest, written instead of the ones written by
+    /// `input()`.
+    pub input: Option<PathBuf>,
+    /// Format of `input`, by default the format of its extension.
+    pub input_format: Option<InputFormat>,
+    /// Directory the proof artifacts are saved to, within a subdirectory for the zkVM.
+    pub proof_data_dir: PathBuf,
+    /// Collects telemetry of the run, returned in the `ProveOutcome`.
+    pub enable_telemetry: bool,
+    /// Directory the telemetry is also saved to, including for failed runs.
+    pub telemetry_output_dir: Option<PathBuf>,
+    /// Keeps the workspace of the run after it finishes.
+    pub keep_workspace: bool,
+    /// Runs the program natively first and fails if the public values of the proof differ from
+    /// the values it commits.
+    pub check_native: bool,
+}
+
+impl ProveConfig {
+    /// Configuration proving the program in `guest_path` with `system`, saving the artifacts to
+    /// `./proof_data`, as a compressed proof without precompiles, GPU acceleration or telemetry.
+    pub fn new(guest_path: impl Into<PathBuf>, system: SystemArg) -> Self {
+        Self {
+            guest_path: guest_path.into(),
+            system,
+            precompiles: false,
+            gpu: false,
+            proof_mode: ProofMode::default(),
+            mock: false,
+            input: None,
+            input_format: None,
+            proof_data_dir: PathBuf::from("./proof_data"),
+            enable_telemetry: false,
+            telemetry_output_dir: None,
+            keep_workspace: false,
+            check_native: false,
+        }
+    }
+}
+
+/// Result of a successful proof generation.
+pub struct ProveOutcome {
+    /// Paths of the proof, program, public input and metrics saved by the host.
+    pub artifacts: ArtifactPaths,
+    /// Metrics written by the host, `None` if they could not be read.
+    pub metrics: Option<Metrics>,
+    /// Telemetry of the run, if enabled.
+    pub telemetry: Option<TelemetryData>,
+    /// Public values decoded to JSON, one entry per commit, `None` if the commit types could not
+    /// be resolved.
+    pub output: Option<serde_json::Value>,
+}
+
+/// Generates a proof of execution of the program in `config.guest_path`, returning the paths of
+/// its artifacts and its metrics.
+pub fn prove(config: &ProveConfig) -> Result<ProveOutcome> {
+    let vm = config.system.zkvm();
+    info!(
+        "Proving with {}, program in: {}",
+        vm.name(),
+        config.guest_path.display()
+    );
+
+    if !vm.proof_modes().contains(&config.proof_mode) {
+        return Err(ZkRustError::UnsupportedProofMode {
+            system: vm.name(),
+            mode: config.proof_mode,
+        });
+    }
+    check_support(vm, &config.guest_path, config.mock)?;
+
+    // Perform sanitation checks on directory
+    utils::validate_directory_structure(&config.guest_path)?;
+    let input = config
+        .input
+        .as_deref()
+        .map(|path| ProgramInput::new(path, config.input_format))
+        .transpose()?;
+    let home_dir = zkrust_home()?;
+    let proof_data_dir = proof_data_dir(&config.proof_data_dir)?;
+    info!("Saving Proofs to: {}", proof_data_dir.display());
+    let layout = vm.layout();
+    let artifacts = vm.artifacts().paths(&proof_data_dir);
+
+    // Run the program natively before proving, so it fails early if it does not run
+    let native = if config.check_native {
+        Some(native::run(
+            &config.guest_path,
+            input.as_ref(),
+            &home_dir,
+            &proof_data_dir,
+            config.keep_workspace,
+        )?)
+    } else {
+        None
+    };
+
+    let workspace_start = Instant::now();
+    let run = RunWorkspace::create(vm, &home_dir, config.keep_workspace)?;
+
+    let telemetry = TelemetryCollector::new(
+        vm.name(),
+        &run.dir().join(layout.host_dir),
+        config.precompiles,
+        config.gpu,
+        config.enable_telemetry,
+        &config.guest_path.to_string_lossy(),
+    );
+
+    vm.prepare_workspace(&config.guest_path, run.dir())?;
+
+    telemetry.record_workspace_setup(workspace_start.elapsed());
+
+    let compilation_start = Instant::now();
+    let (program, precompiles) = prepare_program(vm, run.dir(), config.precompiles)?;
+    telemetry.record_precompiles(&precompiles);
+    if let Some(input) = &input {
+        input.check(&program)?;
+    }
+
+    // Build the program first
+    build_program(vm, &run, &home_dir, config.precompiles, config.gpu)?;
+    info!("{} program built successfully", vm.name());
+    telemetry.record_compilation(compilation_start.elapsed());
+
+    // Record compiled program size, once the host saved it for zkVMs compiling the guest when
+    // they run
+    if layout.program_elf.is_some() {
+        record_program_size(vm, &telemetry, &run.program_elf());
+    }
+
+    let proof_gen_start = Instant::now();
+
+    // Start resource sampling in a separate thread
+    let tx = telemetry.start_resource_monitoring();
+
+    let result = vm.prove(
+        &run.host_bin(),
+        &run.dir().join(layout.workspace_dir),
+        &artifacts.dir,
+        &ProverOptions {
+            proof_mode: config.proof_mode,
+            use_gpu: config.gpu,
+            mock: config.mock,
+            input,
+        },
+    );
+
+    // Stop resource sampling, also when the host could not be run
+    let _ = tx.send(());
+
+    telemetry.record_proof_generation(proof_gen_start.elapsed());
+
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            // Save telemetry data of the failed run before returning the error
+            let telemetry = telemetry.finalize();
+            if let (Some(telemetry_data), Some(output_dir)) =
+                (&telemetry, &config.telemetry_output_dir)
+            {
+                // Return the error of the host rather than the one saving telemetry
+                if let Err(telemetry_error) = save_telemetry(vm, telemetry_data, output_dir, false)
+                {
+                    error!("Failed to save telemetry: {}", telemetry_error);
+                }
+            }
+            return Err(e.into());
+        }
+    };
+
+    let mut metrics = None;
+    let mut output = None;
+    if result.success() {
+        info!(
+            "{} {} {}proof and program artifacts generated",
+            vm.name(),
+            config.proof_mode,
+            if config.mock { "mock " } else { "" }
+        );
+        if config.proof_mode.is_wrapped() {
+            info!("EVM calldata saved to: {}", artifacts.calldata.display());
+        }
+        if layout.program_elf.is_none() {
+            record_program_size(vm, &telemetry, &artifacts.program);
+        }
+
+        // Read and record metrics
+        match vm.read_metrics(&artifacts.metrics) {
+            Ok(read) => {
+                read.record(&telemetry);
+                metrics = Some(read);
+            }
+            Err(e) => warn!("Failed to read {} metrics: {}", vm.name(), e),
+        }
+
+        // Decode the public values with the types committed by the program
+        match decode_public_values(vm, &program, &artifacts) {
+            Ok(decoded) => {
+                info!("Decoded output saved to: {}", artifacts.output.display());
+                output = Some(decoded);
+            }
+            Err(e) => warn!("Failed to decode {} output: {}", vm.name(), e),
+        }
+    }
+
+    // Save telemetry data, also on failure
+    let telemetry = telemetry.finalize();
+    if let (Some(telemetry_data), Some(output_dir)) = (&telemetry, &config.telemetry_output_dir) {
+        match save_telemetry(vm, telemetry_data, output_dir, result.success()) {
+            Err(e) if !result.success() => error!("Failed to save telemetry: {}", e),
+            saved => saved?,
+        }
+    }
+
+    if !result.success() {
+        return Err(ZkRustError::Prover {
+            system: vm.name(),
+            code: result.code(),
+        });
+    }
+    if let Some(native) = &native {
+        native.check(vm, &artifacts.public_input)?;
+    }
+    Ok(ProveOutcome {
+        artifacts,
+        metrics,
+        telemetry,
+        output,
+    })
+}
+
+/// Generates a proof with the options of the `prove-*` commands, submitting it to Aligned if
+/// requested.
+pub async fn prove_and_submit(system: SystemArg, args: &ProofArgs) -> Result<()> {
+    let vm = system.zkvm();
+    let proving_system_id = vm.proving_system_id();
+    if args.submit_to_aligned && proving_system_id.is_none() {
+        return Err(ZkRustError::Aligned(format!(
+            "Aligned does not verify {} proofs",
+            vm.name()
+        )));
+    }
+    if args.submit_to_aligned && args.proof_mode != ProofMode::Compressed {
+        return Err(ZkRustError::Aligned(format!(
+            "Aligned only verifies compressed proofs, not {} proofs",
+            args.proof_mode
+        )));
+    }
+    if args.submit_to_aligned && args.mock {
+        return Err(ZkRustError::Aligned(
+            "mock proofs cannot be submitted".to_string(),
+        ));
+    }
+    let outcome = prove(&args.prove_config(system))?;
+
+    if let (true, Some(proving_system_id)) = (args.submit_to_aligned, proving_system_id) {
+        if outcome.metrics.as_ref().is_some_and(Metrics::is_mock) {
+            return Err(ZkRustError::Aligned(
+                "mock proofs cannot be submitted".to_string(),
+            ));
+        }
+        submit_proof_to_aligned(
+            &outcome.artifacts.proof,
+            &outcome.artifacts.program,
+            Some(&outcome.artifacts.public_input),
+            args,
+            proving_system_id,
+        )
+        .await
+        .map_err(|e| ZkRustError::Aligned(e.to_string()))?;
+        info!("{} proof submitted and verified on Aligned", vm.name());
+    }
+    Ok(())
+}
+
+/// Fails if `vm` cannot run the program in `guest_path`, or generate a mock proof of it if
+/// `mock` is set.
+pub fn check_support(vm: &dyn ZkVm, guest_path: &Path, mock: bool) -> Result<()> {
+    let unsupported = |feature| {
+        Err(ZkRustError::Unsupported {
+            system: vm.name(),
+            feature,
+        })
+    };
+    if mock && !vm.supports_mock() {
+        return unsupported("mock proofs");
+    }
+    if !vm.supports_lib() && guest_path.join("src").join("lib.rs").is_file() {
+        return unsupported("programs with a src/lib.rs library");
+    }
+    Ok(())
+}
+
+/// Runs the program in `args.guest_path` on the executor of `vm`, without generating a proof.
+pub fn execute(vm: &dyn ZkVm, args: &ExecuteArgs) -> Result<()> {
+    info!(
+        "Executing with {}, program in: {}",
+        vm.name(),
+        args.guest_path
+    );
+
+    check_support(vm, Path::new(&args.guest_path), false)?;
+    utils::validate_directory_structure(Path::new(&args.guest_path))?;
+    let input = args
+        .input_path
+        .as_deref()
+        .map(|path| ProgramInput::new(path, args.input_format))
+        .transpose()?;
+    let home_dir = zkrust_home()?;
+    let proof_data_dir = proof_data_dir(Path::new(&args.proof_data_directory_path))?;
+    let layout = vm.layout();
+    let artifacts = vm.artifacts().paths(&proof_data_dir);
+    let run = RunWorkspace::create(vm, &home_dir, args.keep_workspace)?;
+
+    vm.prepare_workspace(Path::new(&args.guest_path), run.dir())?;
+    let (program, _) = prepare_program(vm, run.dir(), args.precompiles)?;
+    if let Some(input) = &input {
+        input.check(&program)?;
+    }
+    build_program(vm, &run, &home_dir, args.precompiles, false)?;
+
+    let result = vm.execute(
+        &run.host_bin(),
+        &run.dir().join(layout.workspace_dir),
+        &artifacts.dir,
+        input.as_ref(),
+    )?;
+
+    if !result.success() {
+        return Err(ZkRustError::Execution {
+            system: vm.name(),
+            code: result.code(),
+        });
+    }
+
+    let report = vm.read_execution_report(&artifacts.execution_report)?;
+    info!(
+        "{} execution completed in {:?}",
+        vm.name(),
+        report.execution_duration
+    );
+    info!("Cycles: {}", report.cycles);
+    info!("Segments: {}", report.num_segments);
+    for (name, stats) in &report.precompiles {
+        match stats.cycles {
+            Some(cycles) => info!(
+                "Precompile {}: {} calls, {} cycles",
+                name, stats.count, cycles
+            ),
+            None => info!("Precompile {}: {} calls", name, stats.count),
+        }
+    }
+    info!(
+        "Execution report saved to: {}",
+        artifacts.execution_report.display()
+    );
+    Ok(())
+}
+
+/// Verifies a proof saved by a previous run against its program and public values.
+pub fn verify(args: &VerifyArgs) -> Result<()> {
+    let vm = args.system.zkvm();
+    let Some(program_path) = args.elf_path.as_ref().or(args.image_id_path.as_ref()) else {
+        return Err(ZkRustError::Verification(
+            "verifying a proof requires --elf or --image-id".to_string(),
+        ));
+    };
+    info!(
+        "Verifying {} proof: {}",
+        vm.name(),
+        args.proof_path.display()
+    );
+
+    vm.verify(
+        &args.proof_path,
+        program_path,
+        args.pub_input_path.as_deref(),
+    )?;
+
+    info!("{} proof verified successfully", vm.name());
+    if args.pub_input_path.is_some() {
+        info!("Public values match");
+    }
+    Ok(())
+}
+
+/// zkRust home directory holding the zkVM workspaces, `~/.zkRust`.
+pub fn zkrust_home() -> Result<PathBuf> {
+    dirs::home_dir()
+        .map(|home_dir| home_dir.join(".zkRust"))
+        .ok_or(ZkRustError::MissingDirectory("home"))
+}
+
+/// Creates the proof data directory and resolves it against the current directory, as the hosts
+/// are run from their workspace.
+pub fn proof_data_dir(path: &Path) -> Result<PathBuf> {
+    let current_dir =
+        std::env::current_dir().map_err(|_| ZkRustError::MissingDirectory("current"))?;
+    let proof_data_dir = current_dir.join(path);
+    fs::create_dir_all(&proof_data_dir)?;
+    Ok(proof_data_dir)
+}
+
+/// Parses the program copied into the workspace of `vm` and generates its guest and host,
+/// patching the accelerated crates of the guest if `precompiles` is set. Returns the parsed
+/// program and the precompile patches applied.
+fn prepare_program(
+    vm: &dyn ZkVm,
+    root_dir: &Path,
+    precompiles: bool,
+) -> Result<(Program, Vec<AppliedPrecompile>)> {
+    let layout = vm.layout();
+    let program = Program::from_file(&root_dir.join(layout.guest_main))?;
+
+    vm.prepare_guest(&program, root_dir)?;
+    vm.prepare_host(&program, root_dir)?;
+
+    let applied = if precompiles {
+        apply_precompile_patches(vm, root_dir)?
+    } else {
+        Vec::new()
+    };
+    Ok((program, applied))
+}
+
+/// Merges the precompile patches of `vm` matching the crate versions locked for the guest into
+/// its manifest, reporting the ones applied and the accelerated crates left unpatched.
+fn apply_precompile_patches(vm: &dyn ZkVm, root_dir: &Path) -> Result<Vec<AppliedPrecompile>> {
+    if vm.precompile_patches().is_empty() {
+        warn!(
+            "{} has no precompile patches, the guest is not accelerated",
+            vm.name()
+        );
+        return Ok(Vec::new());
+    }
+    let layout = vm.layout();
+    let locked = precompiles::locked_packages(&root_dir.join(layout.guest_dir))?;
+    let selection = precompiles::select(vm.precompile_patches(), &locked);
+    for package in &selection.unsupported {
+        warn!(
+            "{} {} is not accelerated: {} has no precompile patch for this version",
+            package.name,
+            package.version,
+            vm.name()
+        );
+    }
+
+    let (applied, conflicts) = selection.apply(&root_dir.join(layout.guest_cargo_toml))?;
+    for conflict in conflicts {
+        warn!("Precompile patch not applied, {}", conflict);
+    }
+    if applied.is_empty() {
+        info!("No precompile patch applies to the guest dependencies");
+    }
+    for precompile in &applied {
+        info!(
+            "Precompile {} applied: {} {} patched with the {} fork",
+            precompile.precompile,
+            precompile.name,
+            precompile.locked_version,
+            precompile.patch_version
+        );
+    }
+    Ok(applied)
+}
+
+/// Records the commit types of `program` next to the proof and saves its public values decoded
+/// with them.
+fn decode_public_values(
+    vm: &dyn ZkVm,
+    program: &Program,
+    artifacts: &ArtifactPaths,
+) -> Result<serde_json::Value> {
+    output::save_commit_types(program, artifacts)?;
+    let decoded = output::decode_saved(vm, artifacts)?;
+    let json = serde_json::to_string_pretty(&decoded).map_err(io::Error::from)?;
+    fs::write(&artifacts.output, json)?;
+    Ok(decoded)
+}
+
+/// Builds the program of `run` in the target directory shared by the runs of `vm`, holding its
+/// lock until the build outputs are copied into the run. Builds of unchanged programs are reused
+/// from the build cache.
+fn build_program(
+    vm: &dyn ZkVm,
+    run: &RunWorkspace,
+    home_dir: &Path,
+    precompiles: bool,
+    use_gpu: bool,
+) -> Result<()> {
+    let cache = BuildCache::for_run(vm, run, home_dir, precompiles, use_gpu)?;
+    if cache.restore(run)? {
+        info!("Reusing cached {} build {}", vm.name(), cache.key());
+        return Ok(());
+    }
+
+    let _lock = run.lock_target()?;
+    let build_result = vm.build(
+        &run.dir().join(vm.layout().workspace_dir),
+        run.target_dir(),
+        use_gpu,
+    )?;
+    if !build_result.success() {
+        return Err(ZkRustError::Build {
+            system: vm.name(),
+            code: build_result.code(),
+        });
+    }
+    run.take_build_outputs(vm)?;
+    if let Err(e) = cache.store(run) {
+        warn!("Failed to cache {} build: {}", vm.name(), e);
+    }
+    if let Err(e) = cache::evict(home_dir, cache::CACHE_MAX_SIZE) {
+        warn!("Failed to evict cached builds: {}", e);
+    }
+    Ok(())
+}
+
+fn record_program_size(vm: &dyn ZkVm, telemetry: &TelemetryCollector, program_elf: &Path) {
+    if let Ok(metadata) = fs::metadata(program_elf) {
+        telemetry.record_program_size(metadata.len());
+        info!(
+            "Recorded {} program size: {} bytes",
+            vm.name(),
+            metadata.len()
+        );
+    } else {
+        error!("Failed to read {} program size", vm.name());
+    }
+}
+
+fn save_telemetry(
+    vm: &dyn ZkVm,
+    telemetry_data: &TelemetryData,
+    output_dir: &Path,
+    success: bool,
+) -> io::Result<()> {
+    fs::create_dir_all(output_dir)?;
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let package_name = telemetry_data
+        .program
+        .guest_metadata
+        .package_name
+        .as_deref()
+        .unwrap_or("unknown");
+    let instance_type = telemetry_data
+        .system_info
+        .ec2_instance_type
+        .as_deref()
+        .unwrap_or("local");
+    let telemetry_file = output_dir.join(format!(
+        "{}_telemetry_{}_{}_{}_{}.json",
+        vm.id(),
+        package_name,
+        instance_type,
+        timestamp,
+        if success { "success" } else { "failed" }
+    ));
+    fs::write(
+        &telemetry_file,
+        serde_json::to_string_pretty(&telemetry_data)?,
+    )?;
+    info!("Telemetry data saved to: {}", telemetry_file.display());
+    Ok(())
+}
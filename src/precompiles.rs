@@ -0,0 +1,197 @@
+use serde::Serialize;
+use std::{
+    fs, io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::manifest::{self, Conflict};
+
+/// Source of the crates.io packages in lockfiles
+const CRATES_IO_SOURCE: &str = "registry+https://github.com/rust-lang/crates.io-index";
+/// Registry the precompile patches replace crates from
+const PATCHED_REGISTRY: &str = "crates-io";
+
+/// Git branch or tag of a patched fork.
+pub enum GitRef {
+    Branch(&'static str),
+    Tag(&'static str),
+}
+
+/// Fork of a crates.io crate routing an expensive operation through a precompile of a zkVM.
+pub struct PrecompilePatch {
+    /// Name of the crate on crates.io.
+    pub name: &'static str,
+    /// Version of the crate the fork is based on.
+    pub version: &'static str,
+    /// Operation accelerated by the fork, e.g. `sha256`.
+    pub precompile: &'static str,
+    pub git: &'static str,
+    pub git_ref: GitRef,
+}
+
+/// Package locked for the guest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+}
+
+/// Precompile patch applied to the guest.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedPrecompile {
+    pub precompile: String,
+    /// Name of the patched crate.
+    #[serde(rename = "crate")]
+    pub name: String,
+    /// Version of the crate locked for the guest.
+    pub locked_version: String,
+    /// Version of the fork replacing it.
+    pub patch_version: String,
+    /// Key of the entry in the `[patch.crates-io]` section of the guest.
+    #[serde(skip)]
+    key: String,
+}
+
+/// Precompile patches matching the packages locked for a guest.
+#[derive(Default)]
+pub struct PrecompileSelection {
+    pub applied: Vec<AppliedPrecompile>,
+    /// Accelerated crates locked at a version no fork supports, by name and version.
+    pub unsupported: Vec<LockedPackage>,
+    patch: toml::Table,
+}
+
+/// Resolves the dependencies of the guest in `guest_dir`, updating its lockfile, and returns the
+/// crates.io packages locked for it.
+pub fn locked_packages(guest_dir: &Path) -> io::Result<Vec<LockedPackage>> {
+    let status = Command::new("cargo")
+        .args(["metadata", "--format-version", "1"])
+        .current_dir(guest_dir)
+        .stdout(Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "Failed to resolve the dependencies of the guest in {}",
+            guest_dir.display()
+        )));
+    }
+
+    let lockfile = fs::read_to_string(guest_dir.join("Cargo.lock"))?;
+    let lockfile: toml::Table =
+        toml::from_str(&lockfile).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let packages = lockfile
+        .get("package")
+        .and_then(toml::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(packages
+        .iter()
+        .filter(|package| {
+            package.get("source").and_then(toml::Value::as_str) == Some(CRATES_IO_SOURCE)
+        })
+        .filter_map(|package| {
+            Some(LockedPackage {
+                name: package.get("name")?.as_str()?.to_string(),
+                version: package.get("version")?.as_str()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Selects, for each accelerated crate in `locked`, the fork of `patches` closest to its locked
+/// version among the ones Cargo can substitute for it: semver compatible and not older.
+pub fn select(patches: &[PrecompilePatch], locked: &[LockedPackage]) -> PrecompileSelection {
+    let mut selection = PrecompileSelection::default();
+    for package in locked {
+        let forks = patches
+            .iter()
+            .filter(|patch| patch.name == package.name)
+            .collect::<Vec<_>>();
+        if forks.is_empty() {
+            continue;
+        }
+        let Some(locked_version) = parse_version(&package.version) else {
+            selection.unsupported.push(package.clone());
+            continue;
+        };
+        let fork = forks
+            .into_iter()
+            .filter_map(|patch| Some((parse_version(patch.version)?, patch)))
+            .filter(|(version, _)| is_compatible(locked_version, *version))
+            .min_by_key(|(version, _)| *version);
+        match fork {
+            Some((_, patch)) => selection.add(package, patch),
+            None => selection.unsupported.push(package.clone()),
+        }
+    }
+    selection
+}
+
+impl PrecompileSelection {
+    fn add(&mut self, package: &LockedPackage, patch: &PrecompilePatch) {
+        // Several versions of a crate are patched under distinct keys
+        let key = if self.patch.contains_key(patch.name) {
+            format!("{}-{}", patch.name, patch.version.replace('.', "_"))
+        } else {
+            patch.name.to_string()
+        };
+        let (ref_key, reference) = match patch.git_ref {
+            GitRef::Branch(branch) => ("branch", branch),
+            GitRef::Tag(tag) => ("tag", tag),
+        };
+        let entry = toml::Table::from_iter([
+            ("git".to_string(), toml::Value::from(patch.git)),
+            (ref_key.to_string(), toml::Value::from(reference)),
+            ("package".to_string(), toml::Value::from(patch.name)),
+        ]);
+        self.patch.insert(key.clone(), toml::Value::Table(entry));
+        self.applied.push(AppliedPrecompile {
+            precompile: patch.precompile.to_string(),
+            name: patch.name.to_string(),
+            locked_version: package.version.clone(),
+            patch_version: patch.version.to_string(),
+            key,
+        });
+    }
+
+    /// Merges the selected patches into the guest manifest at `manifest_path`. Patches conflicting
+    /// with the ones of the program are not applied, and returned along with the applied ones.
+    pub fn apply(
+        mut self,
+        manifest_path: &Path,
+    ) -> io::Result<(Vec<AppliedPrecompile>, Vec<Conflict>)> {
+        if self.applied.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let patch = toml::Table::from_iter([(
+            PATCHED_REGISTRY.to_string(),
+            toml::Value::Table(self.patch),
+        )]);
+        let conflicts = manifest::merge_patch(manifest_path, &patch)?;
+        self.applied.retain(|applied| {
+            let key = format!("patch.{}.{}", PATCHED_REGISTRY, applied.key);
+            !conflicts.iter().any(|conflict| conflict.key == key)
+        });
+        Ok((self.applied, conflicts))
+    }
+}
+
+/// Major, minor and patch numbers of a version, without its pre-release and build metadata.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.split(['-', '+']).next()?;
+    let mut numbers = version.split('.').map(|number| number.parse().ok());
+    let version = (numbers.next()??, numbers.next()??, numbers.next()??);
+    numbers.next().is_none().then_some(version)
+}
+
+/// Whether a fork of version `fork` satisfies the requirements locked at `locked`, following the
+/// Cargo semver rules: same leftmost non-zero number, and not older.
+fn is_compatible(locked: (u64, u64, u64), fork: (u64, u64, u64)) -> bool {
+    let same_series = match locked {
+        (0, 0, _) => fork.0 == 0 && fork.1 == 0 && fork.2 == locked.2,
+        (0, minor, _) => fork.0 == 0 && fork.1 == minor,
+        (major, _, _) => fork.0 == major,
+    };
+    same_series && fork >= locked
+}
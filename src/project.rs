@@ -0,0 +1,313 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
+
+use crate::workspace;
+
+/// Directory the local crates of a program are copied to, relative to the guest and host
+/// directories
+pub const LOCAL_CRATES_DIR: &str = "local_crates";
+
+/// Tables of a manifest holding the dependencies of a crate, besides the ones under
+/// `target.<cfg>`
+const DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "build-dependencies"];
+
+/// Workspace a crate is a member of, providing the dependencies and package fields it inherits.
+struct Workspace {
+    dir: PathBuf,
+    manifest: Table,
+}
+
+impl Workspace {
+    /// Workspace whose root manifest is in `crate_dir` or the closest of its ancestors.
+    fn find(crate_dir: &Path) -> io::Result<Option<Self>> {
+        for dir in crate_dir.ancestors() {
+            let manifest_path = dir.join("Cargo.toml");
+            if !manifest_path.is_file() {
+                continue;
+            }
+            let manifest = read_manifest(&manifest_path)?;
+            if manifest.contains_key("workspace") {
+                return Ok(Some(Self {
+                    dir: dir.to_path_buf(),
+                    manifest,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn table(&self, key: &str) -> Option<&Table> {
+        self.manifest.get("workspace")?.get(key)?.as_table()
+    }
+}
+
+/// Local crates copied into a guest or host directory, by their original directory.
+struct LocalCrates {
+    dir: PathBuf,
+    copied: HashMap<PathBuf, String>,
+}
+
+/// Copies the local crates the program in `project_dir` depends on, through path dependencies
+/// at any depth, workspace dependencies or patches, into `dest_dir` and returns the manifest of
+/// the program. Dependencies inherited from a workspace are resolved, the `[patch]` and
+/// `[profile]` sections of the workspace root are included and paths are rewritten to the
+/// copies, relative to `dest_dir`.
+pub fn copy_local_crates(project_dir: &Path, dest_dir: &Path) -> io::Result<Table> {
+    let project_dir = fs::canonicalize(project_dir)?;
+    let mut manifest = read_manifest(&project_dir.join("Cargo.toml"))?;
+    let workspace = Workspace::find(&project_dir)?;
+    let mut crates = LocalCrates {
+        dir: dest_dir.join(LOCAL_CRATES_DIR),
+        copied: HashMap::new(),
+    };
+    let prefix = format!("{}/", LOCAL_CRATES_DIR);
+
+    // Patches and profiles only apply from the workspace root, the program patches the same
+    // crates as its workspace
+    let mut patches = Vec::new();
+    if let Some(workspace) = workspace.as_ref().filter(|ws| ws.dir != project_dir) {
+        if let Some(Value::Table(patch)) = workspace.manifest.get("patch") {
+            patches.push((patch.clone(), workspace.dir.clone()));
+        }
+        if let Some(profile) = workspace.manifest.get("profile") {
+            manifest.entry("profile").or_insert_with(|| profile.clone());
+        }
+    }
+    if let Some(Value::Table(patch)) = manifest.remove("patch") {
+        patches.push((patch, project_dir.clone()));
+    }
+    let mut resolved_patch = Table::new();
+    for (patch, dir) in patches {
+        for (source, patched) in patch {
+            let Value::Table(patched) = patched else {
+                continue;
+            };
+            let patched = crates.resolve_dependencies(&patched, &dir, None, &prefix)?;
+            if let Value::Table(resolved) = resolved_patch
+                .entry(source)
+                .or_insert_with(|| Value::Table(Table::new()))
+            {
+                resolved.extend(patched);
+            }
+        }
+    }
+
+    let mut manifest =
+        crates.resolve_manifest(manifest, &project_dir, workspace.as_ref(), &prefix)?;
+    if !resolved_patch.is_empty() {
+        manifest.insert("patch".to_string(), Value::Table(resolved_patch));
+    }
+    Ok(manifest)
+}
+
+/// Name of the library of the program in `project_dir`, as the original crate names it, so
+/// `main.rs` can import it when copied into a guest or host crate. `None` if the program has no
+/// `src/lib.rs`.
+pub fn lib_name(project_dir: &Path) -> io::Result<Option<String>> {
+    if !project_dir.join("src").join("lib.rs").is_file() {
+        return Ok(None);
+    }
+    let manifest = read_manifest(&project_dir.join("Cargo.toml"))?;
+    let name = manifest
+        .get("lib")
+        .and_then(|lib| lib.get("name"))
+        .or_else(|| manifest.get("package")?.get("name"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| io::Error::other("Failed to find the package name in project Cargo.toml"))?;
+    Ok(Some(name.replace('-', "_")))
+}
+
+impl LocalCrates {
+    /// Resolves every entry of a dependency table of the crate in `crate_dir`, copying the local
+    /// crates it points to. Rewritten paths start with `prefix`, the path of the copies relative
+    /// to the crate.
+    fn resolve_dependencies(
+        &mut self,
+        dependencies: &Table,
+        crate_dir: &Path,
+        workspace: Option<&Workspace>,
+        prefix: &str,
+    ) -> io::Result<Table> {
+        dependencies
+            .iter()
+            .map(|(name, dependency)| {
+                let dependency =
+                    self.resolve_dependency(name, dependency, crate_dir, workspace, prefix)?;
+                Ok((name.clone(), dependency))
+            })
+            .collect()
+    }
+
+    fn resolve_dependency(
+        &mut self,
+        name: &str,
+        dependency: &Value,
+        crate_dir: &Path,
+        workspace: Option<&Workspace>,
+        prefix: &str,
+    ) -> io::Result<Value> {
+        let Some(table) = dependency.as_table() else {
+            return Ok(dependency.clone());
+        };
+        let mut table = table.clone();
+        // Paths are relative to the manifest declaring them
+        let mut base_dir = crate_dir;
+
+        if table.get("workspace").and_then(Value::as_bool) == Some(true) {
+            let workspace = workspace.ok_or_else(|| {
+                io::Error::other(format!(
+                    "`{}` is inherited from a workspace, but {} is not in one",
+                    name,
+                    crate_dir.display()
+                ))
+            })?;
+            let inherited = workspace
+                .table("dependencies")
+                .and_then(|dependencies| dependencies.get(name))
+                .ok_or_else(|| {
+                    io::Error::other(format!(
+                        "`{}` is not in the `[workspace.dependencies]` of {}",
+                        name,
+                        workspace.dir.display()
+                    ))
+                })?;
+            table = inherit_dependency(inherited, table);
+            base_dir = &workspace.dir;
+        }
+
+        if let Some(path) = table.get("path").and_then(Value::as_str) {
+            let copy = self.copy_crate(&base_dir.join(path))?;
+            table.insert(
+                "path".to_string(),
+                Value::String(format!("{}{}", prefix, copy)),
+            );
+        }
+        Ok(Value::Table(table))
+    }
+
+    /// Copies the crate in `src_dir` once, resolving its own dependencies, and returns the name
+    /// of the copy.
+    fn copy_crate(&mut self, src_dir: &Path) -> io::Result<String> {
+        let src_dir = fs::canonicalize(src_dir)?;
+        if let Some(name) = self.copied.get(&src_dir) {
+            return Ok(name.clone());
+        }
+
+        let base_name = src_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "crate".to_string());
+        let mut name = base_name.clone();
+        let mut suffix = 1;
+        while self.copied.values().any(|copied| *copied == name) {
+            suffix += 1;
+            name = format!("{}-{}", base_name, suffix);
+        }
+        // Recorded before resolving its dependencies, which may depend on it in turn
+        self.copied.insert(src_dir.clone(), name.clone());
+
+        let dest_dir = self.dir.join(&name);
+        workspace::copy_workspace(&src_dir, &dest_dir)?;
+
+        let manifest_path = dest_dir.join("Cargo.toml");
+        let manifest = read_manifest(&manifest_path)?;
+        let workspace = Workspace::find(&src_dir)?;
+        // Copies are siblings within the local crates directory
+        let manifest = self.resolve_manifest(manifest, &src_dir, workspace.as_ref(), "../")?;
+        fs::write(
+            &manifest_path,
+            toml::to_string(&manifest).map_err(io::Error::other)?,
+        )?;
+        Ok(name)
+    }
+
+    /// Makes the manifest of a copied crate standalone: inherited fields are resolved, local
+    /// dependencies point to their copies, under `prefix`, and the crate is no longer a workspace
+    /// root.
+    fn resolve_manifest(
+        &mut self,
+        mut manifest: Table,
+        crate_dir: &Path,
+        workspace: Option<&Workspace>,
+        prefix: &str,
+    ) -> io::Result<Table> {
+        manifest.remove("workspace");
+        // Only needed to test the crate, and may point to crates that are not copied
+        manifest.remove("dev-dependencies");
+
+        if let (Some(Value::Table(package)), Some(inherited)) = (
+            manifest.get_mut("package"),
+            workspace.and_then(|workspace| workspace.table("package")),
+        ) {
+            for (key, value) in package.iter_mut() {
+                let is_inherited = value.get("workspace").and_then(Value::as_bool) == Some(true);
+                if let (true, Some(inherited)) = (is_inherited, inherited.get(key)) {
+                    *value = inherited.clone();
+                }
+            }
+        }
+
+        for key in DEPENDENCY_TABLES {
+            if let Some(Value::Table(dependencies)) = manifest.get(key) {
+                let resolved =
+                    self.resolve_dependencies(dependencies, crate_dir, workspace, prefix)?;
+                manifest.insert(key.to_string(), Value::Table(resolved));
+            }
+        }
+        if let Some(Value::Table(targets)) = manifest.get_mut("target") {
+            for (_, target) in targets.iter_mut() {
+                let Value::Table(target) = target else {
+                    continue;
+                };
+                target.remove("dev-dependencies");
+                for key in DEPENDENCY_TABLES {
+                    if let Some(Value::Table(dependencies)) = target.get(key) {
+                        let resolved =
+                            self.resolve_dependencies(dependencies, crate_dir, workspace, prefix)?;
+                        target.insert(key.to_string(), Value::Table(resolved));
+                    }
+                }
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// Dependency inherited from `[workspace.dependencies]`, with the `features` and `optional` keys
+/// of the member added to it.
+fn inherit_dependency(inherited: &Value, member: Table) -> Table {
+    let mut dependency = match inherited {
+        Value::Table(table) => table.clone(),
+        version => Table::from_iter([("version".to_string(), version.clone())]),
+    };
+    for (key, value) in member {
+        match (key.as_str(), value) {
+            ("workspace", _) => {}
+            ("features", Value::Array(features)) => {
+                if let Some(Value::Array(inherited)) = dependency.get_mut("features") {
+                    inherited.extend(features);
+                } else {
+                    dependency.insert(key, Value::Array(features));
+                }
+            }
+            (_, value) => {
+                dependency.insert(key, value);
+            }
+        }
+    }
+    dependency
+}
+
+fn read_manifest(path: &Path) -> io::Result<Table> {
+    let contents = fs::read_to_string(path)?;
+    toml::from_str(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
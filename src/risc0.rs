@@ -1,13 +1,30 @@
+use aligned_sdk::core::types::ProvingSystemId;
+use risc0_zkvm::{sha::Digest, Receipt};
 use serde::Deserialize;
 use std::{
+    collections::BTreeMap,
     fs,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, ExitStatus},
     time::Duration,
 };
+use syn::parse_quote;
 
-use crate::utils;
+use crate::{
+    error::{Result, ZkRustError},
+    evm::{self, EvmExport},
+    input::ProgramInput,
+    output::OutputEncoding,
+    precompiles::{GitRef, PrecompilePatch},
+    telemetry::TelemetryCollector,
+    transform::{IoRewrite, Program},
+    utils,
+    zkvm::{
+        host_input_args, ArtifactPaths, Artifacts, Metrics, ProofMode, ProverOptions, SyscallStats,
+        WorkspaceLayout, ZkVm, HOST_EXECUTE_MODE,
+    },
+};
 
 #[derive(Deserialize)]
 pub struct Risc0Metrics {
@@ -19,10 +36,60 @@ pub struct Risc0Metrics {
     pub core_verify_duration: Duration,
     pub compress_prove_duration: Duration,
     pub compress_verify_duration: Duration,
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    /// Size of the wrapped proof as passed to the EVM verifier contracts
+    #[serde(default)]
+    pub wrap_proof_size: usize,
+    #[serde(default)]
+    pub wrap_prove_duration: Duration,
+    #[serde(default)]
+    pub wrap_verify_duration: Duration,
+    /// Set for mock proofs, which are not checked by the verifiers
+    #[serde(default)]
+    pub mock: bool,
+    /// Syscalls invoked by the guest, by name
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    /// Syscalls accelerated by a precompile, among `syscalls`
+    #[serde(default)]
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+impl Risc0Metrics {
+    pub fn record(&self, telemetry: &TelemetryCollector) {
+        // Core proofs are not compressed, every other mode compresses them to a succinct proof
+        let compressed = self.proof_mode != ProofMode::Core;
+        telemetry.record_zk_metrics(
+            Some(self.cycles),
+            Some(self.num_segments),
+            Some(self.core_proof_size),
+            compressed.then_some(self.recursive_proof_size),
+        );
+        telemetry.record_proof_timings(
+            self.core_prove_duration,
+            self.core_verify_duration,
+            compressed.then_some(self.compress_prove_duration),
+            compressed.then_some(self.compress_verify_duration),
+        );
+        telemetry.record_syscalls(&self.syscalls, &self.precompiles);
+        if self.mock {
+            telemetry.record_mock_proof();
+        }
+        if self.proof_mode.is_wrapped() {
+            telemetry.record_wrap_metrics(
+                self.proof_mode,
+                self.wrap_proof_size,
+                self.wrap_prove_duration,
+                self.wrap_verify_duration,
+            );
+        }
+    }
 }
 
 /// RISC0 workspace directories
 pub const RISC0_WORKSPACE_DIR: &str = "workspaces/risc0/";
+pub const RISC0_BASE_DIR: &str = "workspaces/base_files/risc0";
 pub const RISC0_SRC_DIR: &str = "workspaces/risc0/methods/guest";
 pub const RISC0_GUEST_MAIN: &str = "workspaces/risc0/methods/guest/src/main.rs";
 pub const RISC0_HOST_MAIN: &str = "workspaces/risc0/host/src/main.rs";
@@ -31,106 +98,408 @@ pub const RISC0_BASE_GUEST_CARGO_TOML: &str = "workspaces/base_files/risc0/cargo
 pub const RISC0_BASE_HOST: &str = "workspaces/base_files/risc0/host";
 pub const RISC0_BASE_HOST_FILE: &str = "workspaces/base_files/risc0/host";
 pub const RISC0_GUEST_CARGO_TOML: &str = "workspaces/risc0/methods/guest/Cargo.toml";
+pub const RISC0_HOST_DIR: &str = "workspaces/risc0/host";
+pub const RISC0_HOST_CARGO_TOML: &str = "workspaces/risc0/host/Cargo.toml";
+
+// Build outputs, relative to the cargo target directory
+pub const RISC0_PROGRAM_ELF: &str = "riscv-guest/riscv32im-risc0-zkvm-elf/release/method";
+pub const RISC0_HOST_BIN: &str = "release/host";
+
+// Proof data generation paths, relative to the proof data directory
+pub const PROOF_DATA_DIR: &str = "risc0";
+pub const PROOF_FILE_PATH: &str = "risc0.proof";
+pub const IMAGE_ID_FILE_PATH: &str = "risc0.imageid";
+pub const PUBLIC_INPUT_FILE_PATH: &str = "risc0.pub";
+pub const METRICS_FILE_PATH: &str = "risc0_metrics.json";
+pub const EXECUTION_REPORT_FILE_PATH: &str = "risc0_execution.json";
+pub const CALLDATA_FILE_PATH: &str = "risc0_calldata.json";
+pub const COMMIT_TYPES_FILE_PATH: &str = "risc0_commit_types.json";
+pub const OUTPUT_FILE_PATH: &str = "risc0_output.json";
+
+static RISC0_LAYOUT: WorkspaceLayout = WorkspaceLayout {
+    root: RISC0_WORKSPACE_DIR,
+    base_dir: RISC0_BASE_DIR,
+    workspace_dir: RISC0_WORKSPACE_DIR,
+    guest_dir: RISC0_SRC_DIR,
+    guest_main: RISC0_GUEST_MAIN,
+    guest_cargo_toml: RISC0_GUEST_CARGO_TOML,
+    host_dir: RISC0_HOST_DIR,
+    host_main: RISC0_HOST_MAIN,
+    host_cargo_toml: RISC0_HOST_CARGO_TOML,
+    base_guest_cargo_toml: RISC0_BASE_GUEST_CARGO_TOML,
+    base_host_cargo_toml: RISC0_BASE_HOST_CARGO_TOML,
+    base_host: RISC0_BASE_HOST,
+    program_elf: Some(RISC0_PROGRAM_ELF),
+    host_bin: RISC0_HOST_BIN,
+};
 
-// Proof data generation paths
-pub const PROOF_FILE_PATH: &str = "./proof_data/risc0/risc0.proof";
-pub const IMAGE_ID_FILE_PATH: &str = "./proof_data/risc0/risc0.imageid";
-pub const PUBLIC_INPUT_FILE_PATH: &str = "./proof_data/risc0/risc0.pub";
-pub const METRICS_FILE_PATH: &str = "./proof_data/risc0/risc0_metrics.json";
+static RISC0_ARTIFACTS: Artifacts = Artifacts {
+    dir: PROOF_DATA_DIR,
+    proof: PROOF_FILE_PATH,
+    program: IMAGE_ID_FILE_PATH,
+    public_input: PUBLIC_INPUT_FILE_PATH,
+    metrics: METRICS_FILE_PATH,
+    execution_report: EXECUTION_REPORT_FILE_PATH,
+    calldata: CALLDATA_FILE_PATH,
+    commit_types: COMMIT_TYPES_FILE_PATH,
+    output: OUTPUT_FILE_PATH,
+};
 
 //TODO: should we use std or no_std header
 /// RISC0 header added to programs for generating proofs of their execution
 pub const RISC0_GUEST_PROGRAM_HEADER: &str = "#![no_main]\n\nrisc0_zkvm::guest::entry!(main);\n";
 
-/// RISC0 Cargo patch for accelerated SHA-256, K256, and bigint-multiplication circuits
-pub const RISC0_ACCELERATION_IMPORT: &str = "\n[patch.crates-io]\nsha2 = { git = \"https://github.com/risc0/RustCrypto-hashes\", tag = \"sha2-v0.10.6-risczero.0\" }\nk256 = { git = \"https://github.com/risc0/RustCrypto-elliptic-curves\", tag = \"k256/v0.13.1-risczero.1\"  }\ncrypto-bigint = { git = \"https://github.com/risc0/RustCrypto-crypto-bigint\", tag = \"v0.5.5-risczero.0\" }";
+/// RISC0 forks of the crates accelerated by its precompiles, selected for the versions locked for
+/// the guest
+pub const RISC0_PRECOMPILE_PATCHES: &[PrecompilePatch] = &[
+    PrecompilePatch {
+        name: "sha2",
+        version: "0.10.6",
+        precompile: "sha256",
+        git: "https://github.com/risc0/RustCrypto-hashes",
+        git_ref: GitRef::Tag("sha2-v0.10.6-risczero.0"),
+    },
+    PrecompilePatch {
+        name: "sha2",
+        version: "0.10.8",
+        precompile: "sha256",
+        git: "https://github.com/risc0/RustCrypto-hashes",
+        git_ref: GitRef::Tag("sha2-v0.10.8-risczero.0"),
+    },
+    PrecompilePatch {
+        name: "k256",
+        version: "0.13.1",
+        precompile: "secp256k1",
+        git: "https://github.com/risc0/RustCrypto-elliptic-curves",
+        git_ref: GitRef::Tag("k256/v0.13.1-risczero.1"),
+    },
+    PrecompilePatch {
+        name: "crypto-bigint",
+        version: "0.5.5",
+        precompile: "bigint",
+        git: "https://github.com/risc0/RustCrypto-crypto-bigint",
+        git_ref: GitRef::Tag("v0.5.5-risczero.0"),
+    },
+];
+
+/// RISC0 crates the program manifest cannot override in the guest and host manifests
+pub const RISC0_PINNED_CRATES: [&str; 1] = ["risc0-zkvm"];
+
+/// RISC0 EVM verifier contracts, matching the version of the RISC0 zkVM used by the host
+pub const RISC0_EVM_DEPENDENCIES: &[&str] =
+    &["foundry-rs/forge-std", "risc0/risc0-ethereum@v1.2.0"];
+pub const RISC0_EVM_REMAPPINGS: &[&str] = &[
+    "forge-std/=lib/forge-std/src/",
+    "risc0/=lib/risc0-ethereum/contracts/src/",
+    "openzeppelin/=lib/risc0-ethereum/lib/openzeppelin-contracts/",
+];
+
+/// Contract verifying RISC0 receipts of the exported program
+pub const RISC0_EVM_CONTRACT: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {IRiscZeroVerifier} from "risc0/IRiscZeroVerifier.sol";
+
+/// @notice Verifies RISC0 receipts of the program exported by zkRust.
+contract ZkRustVerifier {
+    /// @notice Image ID of the program.
+    bytes32 public constant IMAGE_ID = {{PROGRAM_ID}};
+
+    IRiscZeroVerifier public immutable verifier;
+
+    constructor(IRiscZeroVerifier _verifier) {
+        verifier = _verifier;
+    }
+
+    /// @notice Reverts unless `seal` proves an execution of the program committing `journal`.
+    function verify(bytes calldata journal, bytes calldata seal) external view {
+        verifier.verify(seal, IMAGE_ID, sha256(journal));
+    }
+}
+"#;
+
+/// Foundry test checking the exported receipt against the RISC0 Groth16 verifier
+pub const RISC0_EVM_TEST: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Test} from "forge-std/Test.sol";
+import {IRiscZeroVerifier} from "risc0/IRiscZeroVerifier.sol";
+import {ControlID} from "risc0/groth16/ControlID.sol";
+import {RiscZeroGroth16Verifier} from "risc0/groth16/RiscZeroGroth16Verifier.sol";
+import {ZkRustVerifier} from "../src/ZkRustVerifier.sol";
+
+contract ZkRustVerifierTest is Test {
+    bytes constant JOURNAL = {{PUBLIC_VALUES}};
+    bytes constant SEAL = {{PROOF}};
+
+    ZkRustVerifier verifier;
+
+    function setUp() public {
+        IRiscZeroVerifier groth16Verifier =
+            new RiscZeroGroth16Verifier(ControlID.CONTROL_ROOT, ControlID.BN254_CONTROL_ID);
+        verifier = new ZkRustVerifier(groth16Verifier);
+    }
+
+    function test_VerifyProof() public view {
+        verifier.verify(JOURNAL, SEAL);
+    }
+
+    function test_RevertWhen_JournalTampered() public {
+        vm.expectRevert();
+        verifier.verify(bytes.concat(JOURNAL, hex"00"), SEAL);
+    }
+}
+"#;
 
 /// RISC0 User I/O Markers
 // HOST
-pub const RISC0_ENV_BUILDER: &str = "let env = ExecutorEnv::builder()";
 pub const RISC0_IO_HOST: &str = "risc0_zkvm::ExecutorEnv::builder()";
-pub const RISC0_IO_HOST_BUILD: &str = ".build().unwrap();";
+pub const RISC0_HOST_WRITE: &str = "builder.write";
 
 // GUEST
-pub const RISC0_IO_READ: &str = "risc0_zkvm::guest::env::read();";
+pub const RISC0_IO_READ: &str = "risc0_zkvm::guest::env::read";
 pub const RISC0_IO_WRITE: &str = "risc0_zkvm::guest::env::write";
 pub const RISC0_IO_COMMIT: &str = "risc0_zkvm::guest::env::commit";
-pub const RISC0_IO_OUT: &str = "receipt.journal.decode().unwrap();";
-
-pub fn prepare_host(
-    input: &str,
-    output: &str,
-    imports: &str,
-    host_dir: &PathBuf,
-    host_main: &PathBuf,
-) -> io::Result<()> {
-    let mut host_program = imports.to_string();
+pub const RISC0_IO_OUT: &str = "journal.read";
+
+pub fn prepare_host(program: &Program, host_dir: &PathBuf, host_main: &PathBuf) -> Result<()> {
+    let mut host_program = program.host_items();
     let contents = fs::read_to_string(host_dir)?;
     host_program.push_str(&contents);
 
-    // Insert input body
-    let host_program = host_program.replace(utils::HOST_INPUT, input);
-    // Insert output body
-    let host_program = host_program.replace(utils::HOST_OUTPUT, output);
-
-    // Extract Variable names from host and add them to the ExecutorEnv::builder()
-    let values = utils::extract_regex(
-        host_main,
-        &format!("{}[(](.*?)[)]", regex::escape(utils::IO_WRITE)),
-    )?;
-
-    // Construct new Environment Builder
-    let mut new_builder = RISC0_ENV_BUILDER.to_string();
-    for value in values {
-        new_builder.push_str(&format!(".write({}).unwrap()", value));
-    }
-    new_builder.push_str(".build().unwrap();");
-
-    // Replace environment builder in host with new one
-    let host_program = host_program.replace(
-        "let env = ExecutorEnv::builder().build().unwrap();",
-        &new_builder,
+    // Insert input body, writing each value to the ExecutorEnv builder where zkRust::write is
+    // called
+    let input = program.input_body(&[IoRewrite::try_call(utils::IO_WRITE, RISC0_HOST_WRITE)])?;
+    let host_program = host_program.replace(utils::HOST_INPUT, &input);
+    // Insert output body, replacing zkRust::out()
+    let output = program.output_body(&[IoRewrite::call(utils::IO_OUT, RISC0_IO_OUT)])?;
+    let host_program = host_program.replace(utils::HOST_OUTPUT, &output);
+    // Insert writing of the values given with `--input`
+    let read_inputs = utils::read_inputs_body(
+        program,
+        |ty| parse_quote!(builder.write(&inputs.next::<#ty>()).unwrap();),
     );
-
-    // replace zkRust::out()
-    let host_program = host_program.replace(utils::IO_OUT, RISC0_IO_OUT);
+    let host_program = host_program.replace(utils::HOST_READ_INPUTS, &read_inputs);
 
     let mut file = fs::File::create(host_main)?;
     file.write_all(host_program.as_bytes())?;
-
-    utils::remove_lines(host_main, "zk_rust_io::write(")?;
     Ok(())
 }
 
 /// Build the RISC0 program
-pub fn build_risc0_program(workspace_dir: &PathBuf) -> io::Result<ExitStatus> {
-    Command::new("cargo")
-        .arg("build")
-        .arg("--release")
+pub fn build_risc0_program(
+    workspace_dir: &Path,
+    target_dir: &Path,
+    use_gpu: bool,
+) -> io::Result<ExitStatus> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build").arg("--release");
+
+    if use_gpu {
+        cmd.arg("--features").arg("cuda");
+    }
+
+    cmd.env("CARGO_TARGET_DIR", target_dir)
         .current_dir(workspace_dir)
         .status()
 }
 
-/// Generates RISC0 proof and image ID using pre-built artifacts
+/// Generates RISC0 proof and image ID using pre-built artifacts, saving them to `output_dir`
 pub fn generate_risc0_proof(
-    workspace_dir: &PathBuf,
-    current_dir: &PathBuf,
-    use_gpu: bool,
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    options: &ProverOptions,
 ) -> io::Result<ExitStatus> {
-    let mut cmd = Command::new("cargo");
-    cmd.arg("run").arg("--release");
+    let mut cmd = Command::new(host_bin);
 
-    if use_gpu {
-        cmd.arg("--features").arg("cuda");
+    if options.mock {
+        cmd.env("RISC0_DEV_MODE", "1");
     }
 
-    cmd.arg("--")
-        .arg(current_dir)
+    cmd.arg(output_dir)
+        .arg(options.proof_mode.as_str())
+        .args(host_input_args(options.input.as_ref()))
         .current_dir(workspace_dir)
         .status()
 }
 
-pub fn read_metrics() -> io::Result<Risc0Metrics> {
-    let metrics_str = fs::read_to_string(METRICS_FILE_PATH)?;
+/// Runs the RISC0 executor on the program without generating a proof
+pub fn execute_risc0_program(
+    host_bin: &Path,
+    workspace_dir: &Path,
+    output_dir: &Path,
+    input: Option<&ProgramInput>,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(HOST_EXECUTE_MODE)
+        .args(host_input_args(input))
+        .current_dir(workspace_dir)
+        .status()
+}
+
+/// Verifies a bincode-serialized RISC0 receipt against the image ID saved in `image_id_path`, and
+/// its journal against the contents of `pub_input_path` if given
+pub fn verify_risc0_proof(
+    proof_path: &Path,
+    image_id_path: &Path,
+    pub_input_path: Option<&Path>,
+) -> Result<()> {
+    let receipt: Receipt = bincode::deserialize(&fs::read(proof_path)?)
+        .map_err(|e| ZkRustError::Verification(format!("failed to read RISC0 receipt: {}", e)))?;
+    let image_id = Digest::try_from(fs::read(image_id_path)?.as_slice()).map_err(|_| {
+        ZkRustError::Verification("RISC0 image ID must be 32 bytes long".to_string())
+    })?;
+
+    receipt
+        .verify(image_id)
+        .map_err(|e| ZkRustError::Verification(e.to_string()))?;
+
+    if let Some(pub_input_path) = pub_input_path {
+        utils::check_public_input(&receipt.journal.bytes, pub_input_path)?;
+    }
+    Ok(())
+}
+
+/// Arguments of `IRiscZeroVerifier.verify` saved by the host for Groth16 receipts
+#[derive(Deserialize)]
+pub struct Risc0Calldata {
+    pub seal: String,
+    pub image_id: String,
+    pub journal_digest: String,
+}
+
+/// Foundry project checking a Groth16 receipt with the RISC0 verifier contracts
+pub fn export_risc0_evm(artifacts: &ArtifactPaths) -> Result<EvmExport> {
+    let calldata: Risc0Calldata =
+        serde_json::from_str(&fs::read_to_string(&artifacts.calldata)?)
+            .map_err(|e| ZkRustError::Export(format!("failed to read RISC0 calldata: {}", e)))?;
+    let journal = fs::read(&artifacts.public_input)?;
+    let seal = evm::decode_hex(&calldata.seal)?;
+
+    let values = [
+        (evm::PROGRAM_ID, calldata.image_id.as_str()),
+        (evm::PUBLIC_VALUES, &evm::hex_literal(&journal)),
+        (evm::PROOF, &evm::hex_literal(&seal)),
+    ];
+    Ok(EvmExport {
+        dependencies: RISC0_EVM_DEPENDENCIES,
+        remappings: RISC0_EVM_REMAPPINGS,
+        contract: evm::render(RISC0_EVM_CONTRACT, &values),
+        test: evm::render(RISC0_EVM_TEST, &values),
+        public_values: journal,
+        proof: seal,
+    })
+}
+
+pub fn read_metrics(metrics_path: &Path) -> io::Result<Risc0Metrics> {
+    let metrics_str = fs::read_to_string(metrics_path)?;
     serde_json::from_str(&metrics_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
+
+pub struct Risc0;
+
+impl ZkVm for Risc0 {
+    fn name(&self) -> &'static str {
+        "RISC0"
+    }
+
+    fn id(&self) -> &'static str {
+        "risc0"
+    }
+
+    fn layout(&self) -> &'static WorkspaceLayout {
+        &RISC0_LAYOUT
+    }
+
+    fn guest_header(&self) -> &'static str {
+        RISC0_GUEST_PROGRAM_HEADER
+    }
+
+    fn guest_io(&self) -> Vec<IoRewrite<'static>> {
+        vec![
+            IoRewrite::call(utils::IO_READ, RISC0_IO_READ),
+            IoRewrite::call(utils::IO_COMMIT, RISC0_IO_COMMIT),
+        ]
+    }
+
+    fn prepare_host(&self, program: &Program, root_dir: &Path) -> Result<()> {
+        prepare_host(
+            program,
+            &root_dir.join(RISC0_BASE_HOST),
+            &root_dir.join(RISC0_HOST_MAIN),
+        )
+    }
+
+    fn precompile_patches(&self) -> &'static [PrecompilePatch] {
+        RISC0_PRECOMPILE_PATCHES
+    }
+
+    fn pinned_crates(&self) -> &'static [&'static str] {
+        &RISC0_PINNED_CRATES
+    }
+
+    fn build(
+        &self,
+        workspace_dir: &Path,
+        target_dir: &Path,
+        use_gpu: bool,
+    ) -> io::Result<ExitStatus> {
+        build_risc0_program(workspace_dir, target_dir, use_gpu)
+    }
+
+    fn prove(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        options: &ProverOptions,
+    ) -> io::Result<ExitStatus> {
+        generate_risc0_proof(host_bin, workspace_dir, output_dir, options)
+    }
+
+    /// RISC0 only wraps proofs in Groth16 SNARKs
+    fn proof_modes(&self) -> &'static [ProofMode] {
+        &[ProofMode::Core, ProofMode::Compressed, ProofMode::Groth16]
+    }
+
+    fn execute(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        input: Option<&ProgramInput>,
+    ) -> io::Result<ExitStatus> {
+        execute_risc0_program(host_bin, workspace_dir, output_dir, input)
+    }
+
+    fn verify(
+        &self,
+        proof_path: &Path,
+        program_path: &Path,
+        pub_input_path: Option<&Path>,
+    ) -> Result<()> {
+        verify_risc0_proof(proof_path, program_path, pub_input_path)
+    }
+
+    fn artifacts(&self) -> &'static Artifacts {
+        &RISC0_ARTIFACTS
+    }
+
+    fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::Risc0
+    }
+
+    fn export_evm(&self, artifacts: &ArtifactPaths, _proof_mode: ProofMode) -> Result<EvmExport> {
+        export_risc0_evm(artifacts)
+    }
+
+    fn proving_system_id(&self) -> Option<ProvingSystemId> {
+        Some(ProvingSystemId::Risc0)
+    }
+
+    fn read_metrics(&self, metrics_path: &Path) -> io::Result<Metrics> {
+        read_metrics(metrics_path).map(Metrics::Risc0)
+    }
+}
@@ -1,13 +1,30 @@
+use aligned_sdk::core::types::ProvingSystemId;
 use serde::{Deserialize, Serialize};
+use sp1_sdk::{ProverClient, SP1ProofWithPublicValues};
 use std::{
+    collections::BTreeMap,
     fs,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, ExitStatus},
     time::Duration,
 };
+use syn::parse_quote;
 
-use crate::utils;
+use crate::{
+    error::{Result, ZkRustError},
+    evm::{self, EvmExport},
+    input::ProgramInput,
+    output::OutputEncoding,
+    precompiles::{GitRef, PrecompilePatch},
+    telemetry::TelemetryCollector,
+    transform::{IoRewrite, Program},
+    utils,
+    zkvm::{
+        host_input_args, ArtifactPaths, Artifacts, Metrics, ProofMode, ProverOptions, SyscallStats,
+        WorkspaceLayout, ZkVm, HOST_EXECUTE_MODE,
+    },
+};
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct SP1Metrics {
@@ -19,9 +36,63 @@ pub struct SP1Metrics {
     pub core_verify_duration: Duration,
     pub compress_prove_duration: Duration,
     pub compress_verify_duration: Duration,
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    /// Size of the wrapped proof as passed to the EVM verifier contracts
+    #[serde(default)]
+    pub wrap_proof_size: usize,
+    /// Time to generate the wrapped proof from scratch, as the SDK proves the core and compressed
+    /// stages again before wrapping. The compressed stage is not proven on its own in the wrapped
+    /// proof modes.
+    #[serde(default)]
+    pub wrap_prove_duration: Duration,
+    #[serde(default)]
+    pub wrap_verify_duration: Duration,
+    /// Set for mock proofs, which are not checked by the verifiers
+    #[serde(default)]
+    pub mock: bool,
+    /// Syscalls invoked by the guest, by name
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    /// Syscalls accelerated by a precompile, among `syscalls`
+    #[serde(default)]
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+impl SP1Metrics {
+    pub fn record(&self, telemetry: &TelemetryCollector) {
+        // Only compressed proofs go through the compressed stage on its own
+        let compressed = self.proof_mode == ProofMode::Compressed;
+        telemetry.record_zk_metrics(
+            Some(self.cycles),
+            Some(self.num_segments),
+            Some(self.core_proof_size),
+            compressed.then_some(self.recursive_proof_size),
+        );
+        telemetry.record_proof_timings(
+            self.core_prove_duration,
+            self.core_verify_duration,
+            compressed.then_some(self.compress_prove_duration),
+            compressed.then_some(self.compress_verify_duration),
+        );
+        telemetry.record_syscalls(&self.syscalls, &self.precompiles);
+        if self.mock {
+            telemetry.record_mock_proof();
+        }
+        if self.proof_mode.is_wrapped() {
+            telemetry.record_wrap_metrics(
+                self.proof_mode,
+                self.wrap_proof_size,
+                self.wrap_prove_duration,
+                self.wrap_verify_duration,
+            );
+        }
+    }
 }
 
 /// SP1 workspace directories
+pub const SP1_WORKSPACE_ROOT: &str = "workspaces/sp1";
+pub const SP1_BASE_DIR: &str = "workspaces/base_files/sp1";
 pub const SP1_SCRIPT_DIR: &str = "workspaces/sp1/script";
 pub const SP1_SRC_DIR: &str = "workspaces/sp1/program";
 pub const SP1_GUEST_MAIN: &str = "workspaces/sp1/program/src/main.rs";
@@ -31,49 +102,212 @@ pub const SP1_BASE_HOST_CARGO_TOML: &str = "workspaces/base_files/sp1/cargo_host
 pub const SP1_BASE_HOST: &str = "workspaces/base_files/sp1/host";
 pub const SP1_BASE_HOST_FILE: &str = "workspaces/base_files/sp1/host";
 pub const SP1_GUEST_CARGO_TOML: &str = "workspaces/sp1/program/Cargo.toml";
+pub const SP1_HOST_CARGO_TOML: &str = "workspaces/sp1/script/Cargo.toml";
 
-// Proof data generation paths
-pub const SP1_ELF_PATH: &str = "./proof_data/sp1/sp1.elf";
-pub const SP1_PROOF_PATH: &str = "./proof_data/sp1/sp1.proof";
-pub const SP1_PUB_INPUT_PATH: &str = "./proof_data/sp1/sp1.pub";
-pub const SP1_METRICS_PATH: &str = "./proof_data/sp1/sp1_metrics.json";
+// Build outputs, relative to the cargo target directory
+pub const SP1_PROGRAM_ELF: &str = "elf-compilation/riscv32im-succinct-zkvm-elf/release/method";
+pub const SP1_HOST_BIN: &str = "release/method";
+
+// Proof data generation paths, relative to the proof data directory
+pub const SP1_PROOF_DATA_DIR: &str = "sp1";
+pub const SP1_ELF_PATH: &str = "sp1.elf";
+pub const SP1_PROOF_PATH: &str = "sp1.proof";
+pub const SP1_PUB_INPUT_PATH: &str = "sp1.pub";
+pub const SP1_METRICS_PATH: &str = "sp1_metrics.json";
+pub const SP1_EXECUTION_REPORT_PATH: &str = "sp1_execution.json";
+pub const SP1_CALLDATA_PATH: &str = "sp1_calldata.json";
+pub const SP1_COMMIT_TYPES_PATH: &str = "sp1_commit_types.json";
+pub const SP1_OUTPUT_PATH: &str = "sp1_output.json";
+
+static SP1_LAYOUT: WorkspaceLayout = WorkspaceLayout {
+    root: SP1_WORKSPACE_ROOT,
+    base_dir: SP1_BASE_DIR,
+    workspace_dir: SP1_SCRIPT_DIR,
+    guest_dir: SP1_SRC_DIR,
+    guest_main: SP1_GUEST_MAIN,
+    guest_cargo_toml: SP1_GUEST_CARGO_TOML,
+    host_dir: SP1_SCRIPT_DIR,
+    host_main: SP1_HOST_MAIN,
+    host_cargo_toml: SP1_HOST_CARGO_TOML,
+    base_guest_cargo_toml: SP1_BASE_GUEST_CARGO_TOML,
+    base_host_cargo_toml: SP1_BASE_HOST_CARGO_TOML,
+    base_host: SP1_BASE_HOST,
+    program_elf: Some(SP1_PROGRAM_ELF),
+    host_bin: SP1_HOST_BIN,
+};
+
+static SP1_ARTIFACTS: Artifacts = Artifacts {
+    dir: SP1_PROOF_DATA_DIR,
+    proof: SP1_PROOF_PATH,
+    program: SP1_ELF_PATH,
+    public_input: SP1_PUB_INPUT_PATH,
+    metrics: SP1_METRICS_PATH,
+    execution_report: SP1_EXECUTION_REPORT_PATH,
+    calldata: SP1_CALLDATA_PATH,
+    commit_types: SP1_COMMIT_TYPES_PATH,
+    output: SP1_OUTPUT_PATH,
+};
 
 /// SP1 header added to programs for generating proofs of their execution
 pub const SP1_GUEST_PROGRAM_HEADER: &str = "#![no_main]\nsp1_zkvm::entrypoint!(main);\n";
 
-/// SP1 Cargo patch for accelerated SHA-256, K256, and bigint-multiplication circuits
-pub const SP1_ACCELERATION_IMPORT: &str = "\n[patch.crates-io]\nsha2 = { git = \"https://github.com/sp1-patches/RustCrypto-hashes\", package = \"sha2\", branch = \"patch-sha2-v0.10.8\" }\nsha3 = { git = \"https://github.com/sp1-patches/RustCrypto-hashes\", package = \"sha3\", branch = \"patch-sha3-v0.10.8\" }\ncrypto-bigint = { git = \"https://github.com/sp1-patches/RustCrypto-bigint\", branch = \"patch-v0.5.5\" }\ntiny-keccak = { git = \"https://github.com/sp1-patches/tiny-keccak\", branch = \"patch-v2.0.2\" }\ned25519-consensus = { git = \"https://github.com/sp1-patches/ed25519-consensus\", branch = \"patch-v2.1.0\" }\necdsa-core = { git = \"https://github.com/sp1-patches/signatures\", package = \"ecdsa\", branch = \"patch-ecdsa-v0.16.9\" }\n";
+/// SP1 forks of the crates accelerated by its precompiles, selected for the versions locked for
+/// the guest
+pub const SP1_PRECOMPILE_PATCHES: &[PrecompilePatch] = &[
+    PrecompilePatch {
+        name: "sha2",
+        version: "0.9.8",
+        precompile: "sha256",
+        git: "https://github.com/sp1-patches/RustCrypto-hashes",
+        git_ref: GitRef::Branch("patch-sha2-v0.9.8"),
+    },
+    PrecompilePatch {
+        name: "sha2",
+        version: "0.10.6",
+        precompile: "sha256",
+        git: "https://github.com/sp1-patches/RustCrypto-hashes",
+        git_ref: GitRef::Branch("patch-sha2-v0.10.6"),
+    },
+    PrecompilePatch {
+        name: "sha2",
+        version: "0.10.8",
+        precompile: "sha256",
+        git: "https://github.com/sp1-patches/RustCrypto-hashes",
+        git_ref: GitRef::Branch("patch-sha2-v0.10.8"),
+    },
+    PrecompilePatch {
+        name: "sha3",
+        version: "0.10.8",
+        precompile: "keccak256",
+        git: "https://github.com/sp1-patches/RustCrypto-hashes",
+        git_ref: GitRef::Branch("patch-sha3-v0.10.8"),
+    },
+    PrecompilePatch {
+        name: "crypto-bigint",
+        version: "0.5.5",
+        precompile: "uint256_mul",
+        git: "https://github.com/sp1-patches/RustCrypto-bigint",
+        git_ref: GitRef::Branch("patch-v0.5.5"),
+    },
+    PrecompilePatch {
+        name: "tiny-keccak",
+        version: "2.0.2",
+        precompile: "keccak256",
+        git: "https://github.com/sp1-patches/tiny-keccak",
+        git_ref: GitRef::Branch("patch-v2.0.2"),
+    },
+    PrecompilePatch {
+        name: "ed25519-consensus",
+        version: "2.1.0",
+        precompile: "ed25519",
+        git: "https://github.com/sp1-patches/ed25519-consensus",
+        git_ref: GitRef::Branch("patch-v2.1.0"),
+    },
+    PrecompilePatch {
+        name: "ecdsa",
+        version: "0.16.9",
+        precompile: "secp256k1",
+        git: "https://github.com/sp1-patches/signatures",
+        git_ref: GitRef::Branch("patch-ecdsa-v0.16.9"),
+    },
+];
+
+/// SP1 crates the program manifest cannot override in the guest and host manifests
+pub const SP1_PINNED_CRATES: [&str; 2] = ["sp1-zkvm", "sp1-sdk"];
+
+/// SP1 EVM verifier contracts, matching the version of the SP1 SDK used by the host
+pub const SP1_EVM_DEPENDENCIES: &[&str] =
+    &["foundry-rs/forge-std", "succinctlabs/sp1-contracts@v4.0.0"];
+pub const SP1_EVM_REMAPPINGS: &[&str] = &[
+    "forge-std/=lib/forge-std/src/",
+    "@sp1-contracts/=lib/sp1-contracts/contracts/src/",
+];
+pub const SP1_EVM_VERIFIER: &str = "{{VERIFIER}}";
+pub const SP1_EVM_GROTH16_VERIFIER: &str = "v4.0.0-rc.3/SP1VerifierGroth16.sol";
+pub const SP1_EVM_PLONK_VERIFIER: &str = "v4.0.0-rc.3/SP1VerifierPlonk.sol";
+
+/// Contract verifying SP1 proofs of the exported program
+pub const SP1_EVM_CONTRACT: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
+
+/// @notice Verifies SP1 proofs of the program exported by zkRust.
+contract ZkRustVerifier {
+    /// @notice Verifying key of the program.
+    bytes32 public constant PROGRAM_VKEY = {{PROGRAM_ID}};
+
+    ISP1Verifier public immutable verifier;
+
+    constructor(ISP1Verifier _verifier) {
+        verifier = _verifier;
+    }
+
+    /// @notice Reverts unless `proofBytes` proves an execution of the program committing
+    /// `publicValues`.
+    function verify(bytes calldata publicValues, bytes calldata proofBytes) external view {
+        verifier.verifyProof(PROGRAM_VKEY, publicValues, proofBytes);
+    }
+}
+"#;
+
+/// Foundry test checking the exported proof against the SP1 verifier
+pub const SP1_EVM_TEST: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Test} from "forge-std/Test.sol";
+import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
+import {SP1Verifier} from "@sp1-contracts/{{VERIFIER}}";
+import {ZkRustVerifier} from "../src/ZkRustVerifier.sol";
+
+contract ZkRustVerifierTest is Test {
+    bytes constant PUBLIC_VALUES = {{PUBLIC_VALUES}};
+    bytes constant PROOF = {{PROOF}};
+
+    ZkRustVerifier verifier;
+
+    function setUp() public {
+        verifier = new ZkRustVerifier(ISP1Verifier(address(new SP1Verifier())));
+    }
+
+    function test_VerifyProof() public view {
+        verifier.verify(PUBLIC_VALUES, PROOF);
+    }
+
+    function test_RevertWhen_PublicValuesTampered() public {
+        vm.expectRevert();
+        verifier.verify(bytes.concat(PUBLIC_VALUES, hex"00"), PROOF);
+    }
+}
+"#;
 
 /// SP1 User I/O
 // Host
 pub const SP1_HOST_WRITE: &str = "stdin.write";
-pub const SP1_HOST_READ: &str = "proof.public_values.read();";
+pub const SP1_HOST_READ: &str = "public_values.read";
 
 // Guest
-pub const SP1_IO_READ: &str = "sp1_zkvm::io::read();";
+pub const SP1_IO_READ: &str = "sp1_zkvm::io::read";
 pub const SP1_IO_COMMIT: &str = "sp1_zkvm::io::commit";
 
-pub fn prepare_host(
-    input: &str,
-    output: &str,
-    imports: &str,
-    host_dir: &PathBuf,
-    host_main: &PathBuf,
-) -> io::Result<()> {
-    let mut host_program = imports.to_string();
+pub fn prepare_host(program: &Program, host_dir: &PathBuf, host_main: &PathBuf) -> Result<()> {
+    let mut host_program = program.host_items();
     let contents = fs::read_to_string(host_dir)?;
 
     host_program.push_str(&contents);
 
-    // Insert input body
-    let host_program = host_program.replace(utils::HOST_INPUT, input);
-    // Insert output body
-    let host_program = host_program.replace(utils::HOST_OUTPUT, output);
-
-    // replace zkRust::write
-    let host_program = host_program.replace(utils::IO_WRITE, SP1_HOST_WRITE);
-    // replace zkRust::out()
-    let host_program = host_program.replace(utils::IO_OUT, SP1_HOST_READ);
+    // Insert input body, replacing zkRust::write
+    let input = program.input_body(&[IoRewrite::call(utils::IO_WRITE, SP1_HOST_WRITE)])?;
+    let host_program = host_program.replace(utils::HOST_INPUT, &input);
+    // Insert output body, replacing zkRust::out()
+    let output = program.output_body(&[IoRewrite::call(utils::IO_OUT, SP1_HOST_READ)])?;
+    let host_program = host_program.replace(utils::HOST_OUTPUT, &output);
+    // Insert writing of the values given with `--input`
+    let read_inputs = utils::read_inputs_body(
+        program,
+        |ty| parse_quote!(stdin.write(&inputs.next::<#ty>());),
+    );
+    let host_program = host_program.replace(utils::HOST_READ_INPUTS, &read_inputs);
 
     // Write to host
     let mut file = fs::File::create(host_main)?;
@@ -82,35 +316,230 @@ pub fn prepare_host(
 }
 
 /// Build the SP1 program
-pub fn build_sp1_program(script_dir: &PathBuf) -> io::Result<ExitStatus> {
-    Command::new("cargo")
-        .arg("build")
-        .arg("--release")
+pub fn build_sp1_program(
+    script_dir: &Path,
+    target_dir: &Path,
+    use_gpu: bool,
+) -> io::Result<ExitStatus> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build").arg("--release");
+
+    if use_gpu {
+        cmd.arg("--features").arg("cuda");
+    }
+
+    cmd.env("CARGO_TARGET_DIR", target_dir)
         .current_dir(script_dir)
         .status()
 }
 
-/// Generates SP1 proof and ELF using pre-built artifacts
+/// Generates SP1 proof and ELF using pre-built artifacts, saving them to `output_dir`
 pub fn generate_sp1_proof(
-    script_dir: &PathBuf,
-    current_dir: &PathBuf,
-    use_gpu: bool,
+    host_bin: &Path,
+    script_dir: &Path,
+    output_dir: &Path,
+    options: &ProverOptions,
 ) -> io::Result<ExitStatus> {
-    let mut cmd = Command::new("cargo");
-    cmd.arg("run").arg("--release");
+    let mut cmd = Command::new(host_bin);
 
-    if use_gpu {
-        cmd.arg("--features").arg("cuda");
+    if options.mock {
+        cmd.env("SP1_PROVER", "mock");
+    } else if options.use_gpu {
         cmd.env("SP1_PROVER", "cuda");
     }
 
-    cmd.arg("--")
-        .arg(current_dir)
+    cmd.arg(output_dir)
+        .arg(options.proof_mode.as_str())
+        .args(host_input_args(options.input.as_ref()))
+        .current_dir(script_dir)
+        .status()
+}
+
+/// Runs the SP1 executor on the program without generating a proof
+pub fn execute_sp1_program(
+    host_bin: &Path,
+    script_dir: &Path,
+    output_dir: &Path,
+    input: Option<&ProgramInput>,
+) -> io::Result<ExitStatus> {
+    Command::new(host_bin)
+        .arg(output_dir)
+        .arg(HOST_EXECUTE_MODE)
+        .args(host_input_args(input))
         .current_dir(script_dir)
         .status()
 }
 
-pub fn read_metrics() -> io::Result<SP1Metrics> {
-    let metrics_str = fs::read_to_string(SP1_METRICS_PATH)?;
+/// Verifies a bincode-serialized SP1 proof against the verifying key of the program ELF, and its
+/// public values against the contents of `pub_input_path` if given
+pub fn verify_sp1_proof(
+    proof_path: &Path,
+    elf_path: &Path,
+    pub_input_path: Option<&Path>,
+) -> Result<()> {
+    let proof: SP1ProofWithPublicValues = bincode::deserialize(&fs::read(proof_path)?)
+        .map_err(|e| ZkRustError::Verification(format!("failed to read SP1 proof: {}", e)))?;
+    let elf = fs::read(elf_path)?;
+
+    let client = ProverClient::from_env();
+    let (_, vk) = client.setup(&elf);
+    client
+        .verify(&proof, &vk)
+        .map_err(|e| ZkRustError::Verification(e.to_string()))?;
+
+    if let Some(pub_input_path) = pub_input_path {
+        utils::check_public_input(proof.public_values.as_slice(), pub_input_path)?;
+    }
+    Ok(())
+}
+
+/// Arguments of `ISP1Verifier.verifyProof` saved by the host for wrapped proofs
+#[derive(Deserialize)]
+pub struct SP1Calldata {
+    pub program_vkey: String,
+    pub public_values: String,
+    pub proof_bytes: String,
+}
+
+/// Foundry project checking a Groth16 or PLONK proof with the SP1 verifier contracts
+pub fn export_sp1_evm(artifacts: &ArtifactPaths, proof_mode: ProofMode) -> Result<EvmExport> {
+    let calldata: SP1Calldata = serde_json::from_str(&fs::read_to_string(&artifacts.calldata)?)
+        .map_err(|e| ZkRustError::Export(format!("failed to read SP1 calldata: {}", e)))?;
+    let public_values = evm::decode_hex(&calldata.public_values)?;
+    let proof = evm::decode_hex(&calldata.proof_bytes)?;
+
+    let verifier = if proof_mode == ProofMode::Plonk {
+        SP1_EVM_PLONK_VERIFIER
+    } else {
+        SP1_EVM_GROTH16_VERIFIER
+    };
+    let values = [
+        (evm::PROGRAM_ID, calldata.program_vkey.as_str()),
+        (SP1_EVM_VERIFIER, verifier),
+        (evm::PUBLIC_VALUES, &evm::hex_literal(&public_values)),
+        (evm::PROOF, &evm::hex_literal(&proof)),
+    ];
+    Ok(EvmExport {
+        dependencies: SP1_EVM_DEPENDENCIES,
+        remappings: SP1_EVM_REMAPPINGS,
+        contract: evm::render(SP1_EVM_CONTRACT, &values),
+        test: evm::render(SP1_EVM_TEST, &values),
+        public_values,
+        proof,
+    })
+}
+
+pub fn read_metrics(metrics_path: &Path) -> io::Result<SP1Metrics> {
+    let metrics_str = fs::read_to_string(metrics_path)?;
     serde_json::from_str(&metrics_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
+
+pub struct Sp1;
+
+impl ZkVm for Sp1 {
+    fn name(&self) -> &'static str {
+        "SP1"
+    }
+
+    fn id(&self) -> &'static str {
+        "sp1"
+    }
+
+    fn layout(&self) -> &'static WorkspaceLayout {
+        &SP1_LAYOUT
+    }
+
+    fn guest_header(&self) -> &'static str {
+        SP1_GUEST_PROGRAM_HEADER
+    }
+
+    fn guest_io(&self) -> Vec<IoRewrite<'static>> {
+        vec![
+            IoRewrite::call(utils::IO_READ, SP1_IO_READ),
+            IoRewrite::call(utils::IO_COMMIT, SP1_IO_COMMIT),
+        ]
+    }
+
+    fn prepare_host(&self, program: &Program, root_dir: &Path) -> Result<()> {
+        prepare_host(
+            program,
+            &root_dir.join(SP1_BASE_HOST),
+            &root_dir.join(SP1_HOST_MAIN),
+        )
+    }
+
+    fn precompile_patches(&self) -> &'static [PrecompilePatch] {
+        SP1_PRECOMPILE_PATCHES
+    }
+
+    fn pinned_crates(&self) -> &'static [&'static str] {
+        &SP1_PINNED_CRATES
+    }
+
+    fn build(
+        &self,
+        workspace_dir: &Path,
+        target_dir: &Path,
+        use_gpu: bool,
+    ) -> io::Result<ExitStatus> {
+        build_sp1_program(workspace_dir, target_dir, use_gpu)
+    }
+
+    fn prove(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        options: &ProverOptions,
+    ) -> io::Result<ExitStatus> {
+        generate_sp1_proof(host_bin, workspace_dir, output_dir, options)
+    }
+
+    fn proof_modes(&self) -> &'static [ProofMode] {
+        &[
+            ProofMode::Core,
+            ProofMode::Compressed,
+            ProofMode::Groth16,
+            ProofMode::Plonk,
+        ]
+    }
+
+    fn execute(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        input: Option<&ProgramInput>,
+    ) -> io::Result<ExitStatus> {
+        execute_sp1_program(host_bin, workspace_dir, output_dir, input)
+    }
+
+    fn verify(
+        &self,
+        proof_path: &Path,
+        program_path: &Path,
+        pub_input_path: Option<&Path>,
+    ) -> Result<()> {
+        verify_sp1_proof(proof_path, program_path, pub_input_path)
+    }
+
+    fn artifacts(&self) -> &'static Artifacts {
+        &SP1_ARTIFACTS
+    }
+
+    fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::Bincode
+    }
+
+    fn export_evm(&self, artifacts: &ArtifactPaths, proof_mode: ProofMode) -> Result<EvmExport> {
+        export_sp1_evm(artifacts, proof_mode)
+    }
+
+    fn proving_system_id(&self) -> Option<ProvingSystemId> {
+        Some(ProvingSystemId::SP1)
+    }
+
+    fn read_metrics(&self, metrics_path: &Path) -> io::Result<Metrics> {
+        read_metrics(metrics_path).map(Metrics::Sp1)
+    }
+}
@@ -1,15 +1,20 @@
 use log::{debug, info};
 use nvml_wrapper::Nvml;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration as StdDuration;
 use std::time::{Duration, Instant};
 use sysinfo::System;
 use toml::Value;
 
+use crate::{
+    precompiles::AppliedPrecompile,
+    zkvm::{ProofMode, SyscallStats},
+};
+
 const BYTES_TO_KB: u64 = 1024;
 const EC2_METADATA_TOKEN_URL: &str = "http://169.254.169.254/latest/api/token";
 const EC2_METADATA_INSTANCE_TYPE_URL: &str =
@@ -27,12 +32,17 @@ pub struct CargoMetadata {
 
 #[derive(Default, Serialize, Clone)]
 pub struct ZkMetrics {
-    pub cycles: Option<u64>,                 // Number of VM cycles executed
-    pub num_segments: Option<usize>,         // Number of segments/shards
-    pub core_proof_size: Option<usize>,      // Size of the core proof in bytes
-    pub recursive_proof_size: Option<usize>, // Size of the recursive/compressed proof in bytes
-    pub execution_speed: Option<f64>,        // Cycles per second during proof generation
-    pub compiled_program_size: Option<u64>,  // Size of the compiled program in bytes
+    pub cycles: Option<u64>,                      // Number of VM cycles executed
+    pub num_segments: Option<usize>,              // Number of segments/shards
+    pub core_proof_size: Option<usize>,           // Size of the core proof in bytes
+    pub recursive_proof_size: Option<usize>,      // Size of the recursive/compressed proof in bytes
+    pub execution_speed: Option<f64>,             // Cycles per second during proof generation
+    pub compiled_program_size: Option<u64>,       // Size of the compiled program in bytes
+    pub proof_mode: Option<ProofMode>,            // Stage of the saved proof, for wrapped proofs
+    pub wrap_proof_size: Option<usize>,           // Size of the SNARK-wrapped proof in bytes
+    pub mock: bool,                               // Set for mock proofs, without proving cost
+    pub syscalls: BTreeMap<String, SyscallStats>, // Syscall invocations of the guest, by name
+    pub precompiles: BTreeMap<String, SyscallStats>, // Precompiles among the syscalls
 }
 
 #[derive(Default, Serialize, Clone)]
@@ -44,6 +54,8 @@ pub struct TimingMetrics {
     pub core_verify_duration: Option<Duration>, // Time to verify initial proof
     pub compress_prove_duration: Option<Duration>, // Time to generate compressed/recursive proof
     pub compress_verify_duration: Option<Duration>, // Time to verify compressed/recursive proof
+    pub wrap_prove_duration: Option<Duration>, // Time to wrap the proof in a SNARK
+    pub wrap_verify_duration: Option<Duration>, // Time to verify the wrapped proof
     pub total_duration: Option<Duration>,
 }
 
@@ -58,13 +70,13 @@ pub struct ProgramInfo {
 
 #[derive(Default, Serialize, Clone)]
 pub struct ResourceMetrics {
-    max_memory_kb: u64,
-    min_memory_kb: u64,
-    avg_memory_kb: u64,
-    max_cpu_percent: f32,
-    min_cpu_percent: f32,
-    avg_cpu_percent: f32,
-    samples: usize,
+    pub max_memory_kb: u64,
+    pub min_memory_kb: u64,
+    pub avg_memory_kb: u64,
+    pub max_cpu_percent: f32,
+    pub min_cpu_percent: f32,
+    pub avg_cpu_percent: f32,
+    pub samples: usize,
 }
 
 #[derive(Default, Serialize, Clone)]
@@ -96,6 +108,8 @@ pub struct TelemetryData {
     pub resources: ResourceMetrics,
     pub proving_system: String,
     pub precompiles_enabled: bool,
+    /// Precompile patches applied to the guest, for the crate versions it locks.
+    pub precompiles_applied: Vec<AppliedPrecompile>,
     pub gpu_enabled: bool,
     pub program: ProgramInfo,
     pub zk_metrics: ZkMetrics,
@@ -113,6 +127,7 @@ pub struct TelemetryCollector {
 impl TelemetryCollector {
     pub fn new(
         proving_system: &str,
+        host_dir: &Path,
         precompiles_enabled: bool,
         gpu_enabled: bool,
         enabled: bool,
@@ -132,21 +147,10 @@ impl TelemetryCollector {
         // Extract guest metadata
         let guest_metadata = Self::extract_cargo_metadata(guest_path);
 
-        // Extract host metadata from the appropriate host Cargo.toml
-        let host_metadata = if let Some(home_dir) = dirs::home_dir() {
-            let home_dir = home_dir.join(".zkRust");
-            let host_toml_path = if proving_system == "SP1" {
-                home_dir.join("workspaces/sp1/script/Cargo.toml")
-            } else {
-                home_dir.join("workspaces/risc0/host/Cargo.toml")
-            };
-            if host_toml_path.exists() {
-                Self::extract_cargo_metadata(host_toml_path.parent().unwrap().to_str().unwrap())
-            } else {
-                CargoMetadata::default()
-            }
-        } else {
-            CargoMetadata::default()
+        // Extract host metadata from the host Cargo.toml
+        let host_metadata = match host_dir.to_str() {
+            Some(host_dir) => Self::extract_cargo_metadata(host_dir),
+            None => CargoMetadata::default(),
         };
 
         // Calculate average CPU frequency across all CPUs
@@ -448,6 +452,15 @@ impl TelemetryCollector {
         }
     }
 
+    pub fn record_precompiles(&self, applied: &[AppliedPrecompile]) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.precompiles_applied = applied.to_vec();
+        }
+    }
+
     pub fn record_compilation(&self, duration: Duration) {
         if !self.enabled {
             return;
@@ -539,10 +552,25 @@ impl TelemetryCollector {
                 recursive_proof_size,
                 execution_speed,
                 compiled_program_size,
+                ..Default::default()
             };
         }
     }
 
+    pub fn record_syscalls(
+        &self,
+        syscalls: &BTreeMap<String, SyscallStats>,
+        precompiles: &BTreeMap<String, SyscallStats>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.zk_metrics.syscalls = syscalls.clone();
+            metrics.zk_metrics.precompiles = precompiles.clone();
+        }
+    }
+
     pub fn record_proof_timings(
         &self,
         core_prove: Duration,
@@ -561,6 +589,33 @@ impl TelemetryCollector {
         }
     }
 
+    pub fn record_wrap_metrics(
+        &self,
+        proof_mode: ProofMode,
+        wrap_proof_size: usize,
+        wrap_prove: Duration,
+        wrap_verify: Duration,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.zk_metrics.proof_mode = Some(proof_mode);
+            metrics.zk_metrics.wrap_proof_size = Some(wrap_proof_size);
+            metrics.timing.wrap_prove_duration = Some(wrap_prove);
+            metrics.timing.wrap_verify_duration = Some(wrap_verify);
+        }
+    }
+
+    pub fn record_mock_proof(&self) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.zk_metrics.mock = true;
+        }
+    }
+
     pub fn record_program_size(&self, size: u64) {
         if !self.enabled {
             return;
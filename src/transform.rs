@@ -0,0 +1,1305 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use syn::{
+    parse_quote,
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+    Block, Expr, ExprCall, ExprMethodCall, File, GenericArgument, Ident, Item, ItemFn, Local,
+    Member, Pat, PathArguments, Stmt, Type, UseTree,
+};
+
+use crate::error::Result;
+
+/// Crate whose function calls mark user I/O in zkRust programs
+pub const IO_CRATE: &str = "zk_rust_io";
+
+/// Functions of the `zk_rust_io` crate rewritten for the zkVMs
+pub const IO_FUNCTIONS: [&str; 4] = ["read", "commit", "write", "out"];
+
+pub const MAIN_FN: &str = "main";
+pub const INPUT_FN: &str = "input";
+pub const OUTPUT_FN: &str = "output";
+
+/// Describes how a `zk_rust_io::<function>(..)` call is rewritten for a zkVM.
+pub struct IoRewrite<'a> {
+    /// Name of the `zk_rust_io` function, e.g. `read`.
+    pub function: &'a str,
+    pub target: IoTarget<'a>,
+}
+
+pub enum IoTarget<'a> {
+    /// Replaces the callee and keeps the arguments, e.g. `sp1_zkvm::io::commit` or
+    /// `stdin.write`.
+    Call(&'a str),
+    /// Like `Call`, unwrapping the `Result` returned by the target, e.g. `builder.write`.
+    TryCall(&'a str),
+    /// Replaces the whole call with an expression, e.g. `receipt.journal.decode().unwrap()`.
+    Expr(&'a str),
+}
+
+impl<'a> IoRewrite<'a> {
+    pub fn call(function: &'a str, target: &'a str) -> Self {
+        Self {
+            function,
+            target: IoTarget::Call(target),
+        }
+    }
+
+    pub fn try_call(function: &'a str, target: &'a str) -> Self {
+        Self {
+            function,
+            target: IoTarget::TryCall(target),
+        }
+    }
+
+    pub fn expr(function: &'a str, target: &'a str) -> Self {
+        Self {
+            function,
+            target: IoTarget::Expr(target),
+        }
+    }
+
+    fn apply(&self, call: &ExprCall) -> syn::Result<Expr> {
+        match self.target {
+            IoTarget::Expr(target) => syn::parse_str(target),
+            IoTarget::Call(target) => redirect_call(call, target),
+            IoTarget::TryCall(target) => {
+                let call = redirect_call(call, target)?;
+                Ok(parse_quote!(#call.unwrap()))
+            }
+        }
+    }
+}
+
+/// Calls `target` with the arguments and turbofish of `call`.
+fn redirect_call(call: &ExprCall, target: &str) -> syn::Result<Expr> {
+    let turbofish = match &*call.func {
+        Expr::Path(path) => path.path.segments.last().and_then(|s| match &s.arguments {
+            PathArguments::AngleBracketed(args) => Some(args.clone()),
+            _ => None,
+        }),
+        _ => None,
+    };
+
+    match syn::parse_str::<Expr>(target)? {
+        Expr::Path(mut path) => {
+            if let Some(turbofish) = turbofish {
+                if let Some(last) = path.path.segments.last_mut() {
+                    last.arguments = PathArguments::AngleBracketed(turbofish);
+                }
+            }
+            Ok(Expr::Call(ExprCall {
+                attrs: call.attrs.clone(),
+                func: Box::new(Expr::Path(path)),
+                paren_token: call.paren_token,
+                args: call.args.clone(),
+            }))
+        }
+        Expr::Field(field) => {
+            let Member::Named(method) = field.member else {
+                return Err(syn::Error::new_spanned(field, "expected a method name"));
+            };
+            Ok(Expr::MethodCall(ExprMethodCall {
+                attrs: call.attrs.clone(),
+                receiver: field.base,
+                dot_token: field.dot_token,
+                method,
+                turbofish: turbofish.map(|args| syn::AngleBracketedGenericArguments {
+                    colon2_token: Some(Default::default()),
+                    ..args
+                }),
+                paren_token: call.paren_token,
+                args: call.args.clone(),
+            }))
+        }
+        other => Err(syn::Error::new_spanned(
+            other,
+            "I/O target must be a path or a method",
+        )),
+    }
+}
+
+/// Names the `zk_rust_io` functions are called by in a program, resolved from its `use` items.
+#[derive(Debug, Clone)]
+pub struct IoImports {
+    /// Names the crate is called by, `zk_rust_io` and its renamed imports.
+    crates: Vec<String>,
+    /// Functions imported by name, e.g. `r` for `use zk_rust_io::read as r`.
+    functions: HashMap<String, String>,
+    /// Whether the crate is glob imported with `use zk_rust_io::*`.
+    glob: bool,
+    /// Functions declared by the program, which shadow glob imports.
+    local_fns: HashSet<String>,
+}
+
+impl Default for IoImports {
+    fn default() -> Self {
+        Self {
+            crates: vec![IO_CRATE.to_string()],
+            functions: HashMap::new(),
+            glob: false,
+            local_fns: HashSet::new(),
+        }
+    }
+}
+
+impl IoImports {
+    /// Resolves the imports of `zk_rust_io` among the top-level items of a program.
+    pub fn new(items: &[Item]) -> Self {
+        let mut imports = Self::default();
+        for item in items {
+            match item {
+                Item::Use(item) => imports.add_use(&item.tree, false),
+                Item::ExternCrate(item) if item.ident == IO_CRATE => {
+                    if let Some((_, rename)) = &item.rename {
+                        imports.crates.push(rename.to_string());
+                    }
+                }
+                Item::Fn(func) => {
+                    imports.local_fns.insert(func.sig.ident.to_string());
+                }
+                _ => {}
+            }
+        }
+        imports
+    }
+
+    /// Adds the names imported by `tree`, a path within `zk_rust_io` if `in_crate` is set.
+    fn add_use(&mut self, tree: &UseTree, in_crate: bool) {
+        match tree {
+            UseTree::Path(path) if !in_crate && path.ident == IO_CRATE => {
+                self.add_use(&path.tree, true)
+            }
+            UseTree::Rename(rename) if !in_crate && rename.ident == IO_CRATE => {
+                self.crates.push(rename.rename.to_string())
+            }
+            UseTree::Name(name) if in_crate && name.ident != "self" => {
+                let name = name.ident.to_string();
+                self.functions.insert(name.clone(), name);
+            }
+            UseTree::Rename(rename) if in_crate && rename.ident == "self" => {
+                self.crates.push(rename.rename.to_string())
+            }
+            UseTree::Rename(rename) if in_crate => {
+                self.functions
+                    .insert(rename.rename.to_string(), rename.ident.to_string());
+            }
+            UseTree::Glob(_) if in_crate => self.glob = true,
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.add_use(tree, in_crate);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the name of the `zk_rust_io` function called by `call`, if any.
+    pub fn function(&self, call: &ExprCall) -> Option<String> {
+        let Expr::Path(path) = &*call.func else {
+            return None;
+        };
+        let segments = &path.path.segments;
+        match segments.len() {
+            1 => {
+                let name = segments[0].ident.to_string();
+                if let Some(function) = self.functions.get(&name) {
+                    return Some(function.clone());
+                }
+                (self.glob
+                    && IO_FUNCTIONS.contains(&name.as_str())
+                    && !self.local_fns.contains(&name))
+                .then_some(name)
+            }
+            2 if self.crates.iter().any(|name| segments[0].ident == name) => {
+                Some(segments[1].ident.to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+struct IoRewriter<'a, 'b> {
+    rules: &'a [IoRewrite<'b>],
+    imports: &'a IoImports,
+    error: Option<syn::Error>,
+}
+
+impl VisitMut for IoRewriter<'_, '_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        let Some(function) = self.imports.function(call) else {
+            return;
+        };
+        let Some(rule) = self.rules.iter().find(|rule| rule.function == function) else {
+            return;
+        };
+        match rule.apply(call) {
+            Ok(rewritten) => *expr = rewritten,
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+    }
+}
+
+/// Rewrites every `zk_rust_io` call visited by `visit` according to `rules`.
+fn rewrite_io(
+    rules: &[IoRewrite],
+    imports: &IoImports,
+    visit: impl FnOnce(&mut IoRewriter),
+) -> Result<()> {
+    let mut rewriter = IoRewriter {
+        rules,
+        imports,
+        error: None,
+    };
+    visit(&mut rewriter);
+    match rewriter.error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+/// Parsed zkRust program: the `main()`, `input()` and `output()` functions plus every other item
+/// declared alongside them.
+pub struct Program {
+    file: File,
+    imports: IoImports,
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Self> {
+        let file: File = syn::parse_file(source)?;
+        let imports = IoImports::new(&file.items);
+        let program = Self { file, imports };
+        if program.function(MAIN_FN).is_none() {
+            return Err(
+                syn::Error::new(program.file.span(), "`fn main()` not found in program").into(),
+            );
+        }
+        Ok(program)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn function(&self, name: &str) -> Option<&ItemFn> {
+        self.file.items.iter().find_map(|item| match item {
+            Item::Fn(func) if func.sig.ident == name => Some(func),
+            _ => None,
+        })
+    }
+
+    /// Items other than `main()`, `input()` and `output()`.
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        self.file.items.iter().filter(|item| {
+            !matches!(item, Item::Fn(func) if [MAIN_FN, INPUT_FN, OUTPUT_FN]
+                .iter()
+                .any(|name| func.sig.ident == name))
+        })
+    }
+
+    /// Generates the guest program: `header` followed by every item except `input()` and
+    /// `output()`, with `main()` wrapped in cycle tracking and I/O calls rewritten by `rules`.
+    pub fn guest_source(&self, header: &str, rules: &[IoRewrite]) -> Result<String> {
+        let header: File = syn::parse_str(header)?;
+
+        let mut items = header.items;
+        for item in &self.file.items {
+            match item {
+                Item::Fn(func) if func.sig.ident == MAIN_FN => {
+                    items.push(Item::Fn(guest_main(func)));
+                }
+                Item::Fn(func) if func.sig.ident == INPUT_FN || func.sig.ident == OUTPUT_FN => {}
+                item => items.push(item.clone()),
+            }
+        }
+
+        let mut guest = File {
+            shebang: None,
+            attrs: header
+                .attrs
+                .into_iter()
+                .chain(self.file.attrs.clone())
+                .collect(),
+            items,
+        };
+        rewrite_io(rules, &self.imports, |rewriter| {
+            rewriter.visit_file_mut(&mut guest)
+        })?;
+        Ok(prettyplease::unparse(&guest))
+    }
+
+    /// Renders the items shared with the host: everything except `main()`, `input()` and
+    /// `output()`. Inner attributes of the program are dropped.
+    pub fn host_items(&self) -> String {
+        prettyplease::unparse(&File {
+            shebang: None,
+            attrs: Vec::new(),
+            items: self.items().cloned().collect(),
+        })
+    }
+
+    /// Body of `input()` with I/O calls rewritten by `rules`, empty if `input()` is not defined.
+    pub fn input_body(&self, rules: &[IoRewrite]) -> Result<String> {
+        self.body(INPUT_FN, rules)
+    }
+
+    /// Body of `output()` with I/O calls rewritten by `rules`, empty if `output()` is not
+    /// defined.
+    pub fn output_body(&self, rules: &[IoRewrite]) -> Result<String> {
+        self.body(OUTPUT_FN, rules)
+    }
+
+    /// Generates the native program: every item except `input()` and `output()`, with `main()`
+    /// renamed to `main_name` and I/O calls rewritten by `rules` across the whole file. Inner
+    /// attributes of the program are dropped.
+    pub fn native_source(&self, main_name: &str, rules: &[IoRewrite]) -> Result<String> {
+        let mut native = File {
+            shebang: None,
+            attrs: Vec::new(),
+            items: self
+                .file
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Fn(func) if func.sig.ident == MAIN_FN => {
+                        let mut main = func.clone();
+                        main.sig.ident = Ident::new(main_name, main.sig.ident.span());
+                        Some(Item::Fn(main))
+                    }
+                    Item::Fn(func) if func.sig.ident == INPUT_FN || func.sig.ident == OUTPUT_FN => {
+                        None
+                    }
+                    item => Some(item.clone()),
+                })
+                .collect(),
+        };
+        rewrite_io(rules, &self.imports, |rewriter| {
+            rewriter.visit_file_mut(&mut native)
+        })?;
+        Ok(prettyplease::unparse(&native))
+    }
+
+    /// Types of the values read by `main()` with `zk_rust_io::<function>()`, in order, including
+    /// the reads of the functions of the program it calls. Each call must be annotated with its
+    /// type, either as `let x: T = ..` or with a turbofish. Fails if the number of reads cannot be
+    /// determined statically, as for reads within loops, closures or conditionals.
+    pub fn read_types(&self, function: &str) -> Result<Vec<Type>> {
+        self.io_types(MAIN_FN, function)
+    }
+
+    /// Types of the values committed by `main()` with `zk_rust_io::<commit>()`, in order,
+    /// including the commits of the functions of the program it calls. The type of each call is
+    /// given by its turbofish or by the `let x: T` binding or parameter type of the committed
+    /// variable. Otherwise the types read by `output()` with `zk_rust_io::<out>()` are used, if
+    /// they hold one type per commit, either as one call per commit or as a single tuple. Fails
+    /// if the number of commits cannot be determined statically, as for `read_types()`.
+    pub fn commit_types(&self, commit: &str, out: &str) -> Result<Vec<Type>> {
+        let types = self
+            .io_calls(MAIN_FN, commit)?
+            .into_iter()
+            .map(|io_call| turbofish_type(&io_call.call).or(io_call.arg_type))
+            .collect::<Vec<_>>();
+        if let Some(types) = types.iter().cloned().collect::<Option<Vec<_>>>() {
+            return Ok(types);
+        }
+
+        let commits = types.len();
+        let out_types = self.io_types(OUTPUT_FN, out).unwrap_or_default();
+        match out_types.as_slice() {
+            types if types.len() == commits => Ok(out_types),
+            [Type::Tuple(tuple)] if tuple.elems.len() == commits => {
+                Ok(tuple.elems.iter().cloned().collect())
+            }
+            _ => Err(syn::Error::new(
+                self.file.span(),
+                format!(
+                    "type of `{}::{}()` must be annotated, e.g. `{}::{}::<u32>(&x)`, or read with `{}::{}()` in `output()`",
+                    IO_CRATE, commit, IO_CRATE, commit, IO_CRATE, out
+                ),
+            )
+            .into()),
+        }
+    }
+
+    /// Types of the values read by `fn_name` with `zk_rust_io::<function>()`, in order.
+    fn io_types(&self, fn_name: &str, function: &str) -> Result<Vec<Type>> {
+        self.io_calls(fn_name, function)?
+            .into_iter()
+            .map(|io_call| {
+                turbofish_type(&io_call.call)
+                    .or(io_call.binding)
+                    .ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            &io_call.call,
+                            format!(
+                                "type of `{}::{}()` must be annotated, e.g. `let x: u32 = {}::{}();`",
+                                IO_CRATE, function, IO_CRATE, function
+                            ),
+                        )
+                        .into()
+                    })
+            })
+            .collect()
+    }
+
+    /// Calls of `zk_rust_io::<function>()` made by `fn_name`, in order, following the calls of
+    /// the other functions of the program. Fails if a call is made a number of times that cannot
+    /// be determined statically, or from code `fn_name` does not call by name.
+    fn io_calls(&self, fn_name: &str, function: &str) -> Result<Vec<IoCall>> {
+        let mut collector = IoCallCollector {
+            program: self,
+            function,
+            bindings: HashMap::new(),
+            stack: Vec::new(),
+            reached: HashSet::new(),
+            dynamic: None,
+            calls: Vec::new(),
+            error: None,
+        };
+        if let Some(func) = self.function(fn_name) {
+            collector.expand(func);
+        }
+        if let Some(e) = collector.error {
+            return Err(e.into());
+        }
+
+        if let Some(call) = self.unreached_io_call(fn_name, function, &collector.reached) {
+            return Err(syn::Error::new_spanned(
+                &call,
+                format!(
+                    "`{}::{}()` must be called from `{}()` or the functions it calls by name, so the number of calls can be determined",
+                    IO_CRATE, function, fn_name
+                ),
+            )
+            .into());
+        }
+        Ok(collector.calls)
+    }
+
+    /// A call of `zk_rust_io::<function>()` in code not visited when following the calls of
+    /// `fn_name`: other entry points are skipped, the functions in `reached` are only searched for
+    /// nested items and every other item is searched entirely.
+    fn unreached_io_call(
+        &self,
+        fn_name: &str,
+        function: &str,
+        reached: &HashSet<String>,
+    ) -> Option<ExprCall> {
+        let mut finder = IoCallFinder {
+            imports: &self.imports,
+            function,
+            found: None,
+        };
+        for item in &self.file.items {
+            match item {
+                Item::Fn(func) if reached.contains(&func.sig.ident.to_string()) => {
+                    let mut items = NestedItems::default();
+                    items.visit_block_mut(&mut (*func.block).clone());
+                    for mut item in items.items {
+                        finder.visit_item_mut(&mut item);
+                    }
+                }
+                Item::Fn(func)
+                    if func.sig.ident != fn_name
+                        && [MAIN_FN, INPUT_FN, OUTPUT_FN]
+                            .iter()
+                            .any(|name| func.sig.ident == name) => {}
+                item => finder.visit_item_mut(&mut item.clone()),
+            }
+        }
+        finder.found
+    }
+
+    /// Function of the program called by `call` by name, e.g. `helper` for `helper(x)`.
+    fn called_function(&self, call: &ExprCall) -> Option<&ItemFn> {
+        let Expr::Path(path) = &*call.func else {
+            return None;
+        };
+        let ident = path.path.get_ident()?;
+        self.function(&ident.to_string())
+    }
+
+    fn body(&self, fn_name: &str, rules: &[IoRewrite]) -> Result<String> {
+        let Some(func) = self.function(fn_name) else {
+            return Ok(String::new());
+        };
+        let mut block = (*func.block).clone();
+        rewrite_io(rules, &self.imports, |rewriter| {
+            rewriter.visit_block_mut(&mut block)
+        })?;
+        Ok(render_block(&block))
+    }
+}
+
+/// Call of a `zk_rust_io` function, as made by an entry point of the program.
+struct IoCall {
+    call: ExprCall,
+    /// Type of the `let x: T` binding initialized by the call.
+    binding: Option<Type>,
+    /// Type of the variable passed by reference as the first argument, without references.
+    arg_type: Option<Type>,
+}
+
+/// Collects the calls of a `zk_rust_io` function in the order they are made, expanding the calls
+/// of the functions of the program in place. Calls made within loops, closures, conditionals or
+/// recursive functions are errors, as the number of times they are made is only known at runtime.
+struct IoCallCollector<'a> {
+    program: &'a Program,
+    function: &'a str,
+    /// Types of the variables in scope bound with `let x: T` or as parameters. Variables bound
+    /// without a type shadow those of the same name.
+    bindings: HashMap<String, Type>,
+    /// Functions being expanded, innermost last.
+    stack: Vec<String>,
+    /// Functions expanded.
+    reached: HashSet<String>,
+    /// Construct the visited code is within that repeats or skips it, e.g. `a loop`.
+    dynamic: Option<&'static str>,
+    calls: Vec<IoCall>,
+    error: Option<syn::Error>,
+}
+
+impl IoCallCollector<'_> {
+    fn is_io_call(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Call(call) if self.program.imports.function(call).as_deref() == Some(self.function))
+    }
+
+    fn fail(&mut self, tokens: impl quote::ToTokens, reason: &str) {
+        let mut message = format!(
+            "the number of calls to `{}::{}()` cannot be determined, it is called {}",
+            IO_CRATE, self.function, reason
+        );
+        if let Some(name) = self.stack.last().filter(|_| self.stack.len() > 1) {
+            message.push_str(&format!(", through `{}()`", name));
+        }
+        self.error
+            .get_or_insert(syn::Error::new_spanned(tokens, message));
+    }
+
+    fn record(&mut self, call: &ExprCall, binding: Option<Type>) {
+        if let Some(construct) = self.dynamic {
+            self.fail(call, construct);
+            return;
+        }
+        let arg_type = self.arg_type(call);
+        self.calls.push(IoCall {
+            call: call.clone(),
+            binding,
+            arg_type,
+        });
+    }
+
+    /// Type of the variable passed as the first argument of `call`, e.g. `T` for `&x` if `x` is
+    /// bound as `x: T` or `x: &T`.
+    fn arg_type(&self, call: &ExprCall) -> Option<Type> {
+        let mut arg = call.args.first()?;
+        while let Expr::Reference(reference) = arg {
+            arg = &reference.expr;
+        }
+        let Expr::Path(path) = arg else {
+            return None;
+        };
+        let mut ty = self.bindings.get(&path.path.get_ident()?.to_string())?;
+        while let Type::Reference(reference) = ty {
+            ty = &reference.elem;
+        }
+        Some(ty.clone())
+    }
+
+    /// Visits the body of `func` with the bindings of its parameters.
+    fn expand(&mut self, func: &ItemFn) {
+        let name = func.sig.ident.to_string();
+        if self.stack.contains(&name) {
+            if self.reaches_io_call(func, &mut HashSet::new()) {
+                self.fail(func.sig.ident.clone(), "by a recursive function");
+            }
+            return;
+        }
+        self.reached.insert(name.clone());
+
+        let mut bindings = HashMap::new();
+        for input in &func.sig.inputs {
+            if let syn::FnArg::Typed(arg) = input {
+                if let Pat::Ident(ident) = &*arg.pat {
+                    bindings.insert(ident.ident.to_string(), (*arg.ty).clone());
+                }
+            }
+        }
+        let bindings = std::mem::replace(&mut self.bindings, bindings);
+        self.stack.push(name);
+        self.visit_block_mut(&mut (*func.block).clone());
+        self.stack.pop();
+        self.bindings = bindings;
+    }
+
+    /// Whether `func` calls the I/O function, directly or through the functions it calls.
+    fn reaches_io_call(&self, func: &ItemFn, seen: &mut HashSet<String>) -> bool {
+        if !seen.insert(func.sig.ident.to_string()) {
+            return false;
+        }
+        let mut finder = IoCallFinder {
+            imports: &self.program.imports,
+            function: self.function,
+            found: None,
+        };
+        finder.visit_item_fn_mut(&mut func.clone());
+        if finder.found.is_some() {
+            return true;
+        }
+        let mut calls = FunctionCalls::default();
+        calls.visit_item_fn_mut(&mut func.clone());
+        calls.calls.iter().any(|call| {
+            self.program
+                .called_function(call)
+                .is_some_and(|callee| self.reaches_io_call(callee, seen))
+        })
+    }
+
+    /// Visits code run a number of times only known at runtime.
+    fn within(&mut self, construct: &'static str, visit: impl FnOnce(&mut Self)) {
+        let dynamic = self.dynamic.replace(construct);
+        visit(self);
+        self.dynamic = dynamic;
+    }
+}
+
+impl VisitMut for IoCallCollector<'_> {
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        match (&mut local.init, &local.pat) {
+            (Some(init), Pat::Type(pat)) if self.is_io_call(&init.expr) => {
+                let Expr::Call(call) = &mut *init.expr else {
+                    unreachable!()
+                };
+                for arg in call.args.iter_mut() {
+                    self.visit_expr_mut(arg);
+                }
+                let call = call.clone();
+                self.record(&call, Some((*pat.ty).clone()));
+            }
+            _ => visit_mut::visit_local_mut(self, local),
+        }
+        let mut bindings = Vec::new();
+        pattern_bindings(&local.pat, None, &mut bindings);
+        for (name, ty) in bindings {
+            match ty {
+                Some(ty) => self.bindings.insert(name, ty),
+                None => self.bindings.remove(&name),
+            };
+        }
+    }
+
+    // Variables bound within a block go out of scope at its end
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let bindings = self.bindings.clone();
+        visit_mut::visit_block_mut(self, block);
+        self.bindings = bindings;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::ForLoop(e) => {
+                self.visit_expr_mut(&mut e.expr);
+                self.within("within a loop", |c| c.visit_block_mut(&mut e.body));
+            }
+            Expr::While(e) => self.within("within a loop", |c| {
+                c.visit_expr_mut(&mut e.cond);
+                c.visit_block_mut(&mut e.body);
+            }),
+            Expr::Loop(e) => self.within("within a loop", |c| c.visit_block_mut(&mut e.body)),
+            Expr::Closure(e) => self.within("within a closure", |c| c.visit_expr_mut(&mut e.body)),
+            Expr::Async(e) => {
+                self.within("within an async block", |c| c.visit_block_mut(&mut e.block))
+            }
+            Expr::If(e) => {
+                self.visit_expr_mut(&mut e.cond);
+                self.within("within a conditional", |c| {
+                    c.visit_block_mut(&mut e.then_branch);
+                    if let Some((_, else_branch)) = &mut e.else_branch {
+                        c.visit_expr_mut(else_branch);
+                    }
+                });
+            }
+            Expr::Match(e) => {
+                self.visit_expr_mut(&mut e.expr);
+                self.within("within a match arm", |c| {
+                    for arm in &mut e.arms {
+                        c.visit_arm_mut(arm);
+                    }
+                });
+            }
+            Expr::Binary(e) if matches!(e.op, syn::BinOp::And(_) | syn::BinOp::Or(_)) => {
+                self.visit_expr_mut(&mut e.left);
+                self.within("within a conditional", |c| c.visit_expr_mut(&mut e.right));
+            }
+            Expr::Call(call) => {
+                visit_mut::visit_expr_call_mut(self, call);
+                if self.program.imports.function(call).as_deref() == Some(self.function) {
+                    self.record(call, None);
+                } else if let Some(callee) = self.program.called_function(call) {
+                    self.expand(callee);
+                }
+            }
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    // Items declared within functions are not run where they are declared
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+}
+
+/// Variables bound by `pat`, with their type if `ty` or an annotation within `pat` gives it,
+/// e.g. `a: u32` and `b: bool` for `(a, b): (u32, bool)`, but only `c` for `Some(c)`.
+fn pattern_bindings(pat: &Pat, ty: Option<&Type>, bindings: &mut Vec<(String, Option<Type>)>) {
+    match pat {
+        Pat::Type(pat) => pattern_bindings(&pat.pat, Some(&pat.ty), bindings),
+        Pat::Ident(ident) => {
+            let ty = ty.filter(|_| ident.subpat.is_none());
+            bindings.push((ident.ident.to_string(), ty.cloned()));
+            if let Some((_, subpat)) = &ident.subpat {
+                pattern_bindings(subpat, None, bindings);
+            }
+        }
+        Pat::Paren(pat) => pattern_bindings(&pat.pat, ty, bindings),
+        Pat::Reference(pat) => {
+            let ty = match ty {
+                Some(Type::Reference(reference)) => Some(&*reference.elem),
+                _ => None,
+            };
+            pattern_bindings(&pat.pat, ty, bindings);
+        }
+        Pat::Tuple(pat) => {
+            let types: Vec<_> = match ty {
+                Some(Type::Tuple(tuple)) if tuple.elems.len() == pat.elems.len() => {
+                    tuple.elems.iter().map(Some).collect()
+                }
+                _ => vec![None; pat.elems.len()],
+            };
+            for (elem, ty) in pat.elems.iter().zip(types) {
+                pattern_bindings(elem, ty, bindings);
+            }
+        }
+        Pat::TupleStruct(pat) => {
+            for elem in &pat.elems {
+                pattern_bindings(elem, None, bindings);
+            }
+        }
+        Pat::Struct(pat) => {
+            for field in &pat.fields {
+                pattern_bindings(&field.pat, None, bindings);
+            }
+        }
+        Pat::Slice(pat) => {
+            for elem in &pat.elems {
+                pattern_bindings(elem, None, bindings);
+            }
+        }
+        // Every case of an or-pattern binds the same variables
+        Pat::Or(pat) => {
+            if let Some(case) = pat.cases.first() {
+                pattern_bindings(case, None, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Finds a call of a `zk_rust_io` function anywhere in the visited code.
+struct IoCallFinder<'a> {
+    imports: &'a IoImports,
+    function: &'a str,
+    found: Option<ExprCall>,
+}
+
+impl VisitMut for IoCallFinder<'_> {
+    fn visit_expr_call_mut(&mut self, call: &mut ExprCall) {
+        visit_mut::visit_expr_call_mut(self, call);
+        if self.found.is_none() && self.imports.function(call).as_deref() == Some(self.function) {
+            self.found = Some(call.clone());
+        }
+    }
+}
+
+/// Collects the function calls of the visited code, except within nested items.
+#[derive(Default)]
+struct FunctionCalls {
+    calls: Vec<ExprCall>,
+}
+
+impl VisitMut for FunctionCalls {
+    fn visit_expr_call_mut(&mut self, call: &mut ExprCall) {
+        visit_mut::visit_expr_call_mut(self, call);
+        self.calls.push(call.clone());
+    }
+
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+}
+
+/// Collects the items declared within the visited code.
+#[derive(Default)]
+struct NestedItems {
+    items: Vec<Item>,
+}
+
+impl VisitMut for NestedItems {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        self.items.push(item.clone());
+    }
+}
+
+/// Type given to `call` with a turbofish, e.g. `T` in `zk_rust_io::read::<T>()`.
+fn turbofish_type(call: &ExprCall) -> Option<Type> {
+    let Expr::Path(path) = &*call.func else {
+        return None;
+    };
+    match &path.path.segments.last()?.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Renders the statements of `block` as source code, without the enclosing braces.
+pub fn render_block(block: &Block) -> String {
+    let func: ItemFn = parse_quote!(fn __zkrust_body() #block);
+    let source = prettyplease::unparse(&File {
+        shebang: None,
+        attrs: Vec::new(),
+        items: vec![Item::Fn(func)],
+    });
+    source
+        .strip_prefix("fn __zkrust_body() {")
+        .and_then(|s| s.strip_suffix("}\n"))
+        .unwrap_or_default()
+        .trim_matches('\n')
+        .to_string()
+}
+
+fn guest_main(main: &ItemFn) -> ItemFn {
+    let mut stmts = main.block.stmts.clone();
+    // A trailing expression is no longer the tail of the block once the cycle tracker report is
+    // appended, so terminate it.
+    if let Some(Stmt::Expr(_, semi @ None)) = stmts.last_mut() {
+        *semi = Some(Default::default());
+    }
+
+    let mut main = main.clone();
+    main.vis = parse_quote!(pub);
+    main.block = parse_quote!({
+        println!("cycle-tracker-report-start: {}", env!("CARGO_PKG_NAME"));
+        #(#stmts)*
+        println!("cycle-tracker-report-end: {}", env!("CARGO_PKG_NAME"));
+    });
+    main
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+
+    use super::*;
+
+    /// Rules rewriting reads and commits to calls easy to find in the generated source.
+    fn guest_rules() -> [IoRewrite<'static>; 2] {
+        [
+            IoRewrite::call("read", "vm_read"),
+            IoRewrite::call("commit", "vm_commit"),
+        ]
+    }
+
+    fn guest(source: &str) -> String {
+        Program::parse(source)
+            .unwrap()
+            .guest_source("", &guest_rules())
+            .unwrap()
+    }
+
+    fn types(types: Vec<Type>) -> Vec<String> {
+        types
+            .iter()
+            .map(|ty| ty.to_token_stream().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn rewrites_calls_through_renamed_crate() {
+        let guest = guest(
+            "use zk_rust_io as io;
+            fn main() {
+                let n: u32 = io::read();
+                io::commit(&n);
+            }",
+        );
+        assert!(guest.contains("let n: u32 = vm_read();"), "{}", guest);
+        assert!(guest.contains("vm_commit(&n);"), "{}", guest);
+    }
+
+    #[test]
+    fn rewrites_calls_through_renamed_functions() {
+        let guest = guest(
+            "use zk_rust_io::{self as io, read as get, commit};
+            fn main() {
+                let a: u32 = get();
+                let b: u32 = io::read();
+                commit(&(a + b));
+            }",
+        );
+        assert!(guest.contains("let a: u32 = vm_read();"), "{}", guest);
+        assert!(guest.contains("let b: u32 = vm_read();"), "{}", guest);
+        assert!(guest.contains("vm_commit(&(a + b));"), "{}", guest);
+    }
+
+    #[test]
+    fn rewrites_glob_imported_calls() {
+        let guest = guest(
+            "use zk_rust_io::*;
+            fn main() {
+                let n: u32 = read();
+                commit(&n);
+                write();
+            }
+            fn write() {}",
+        );
+        assert!(guest.contains("let n: u32 = vm_read();"), "{}", guest);
+        assert!(guest.contains("vm_commit(&n);"), "{}", guest);
+        // Functions of the program shadow the glob import
+        assert!(guest.contains("fn write() {}"), "{}", guest);
+    }
+
+    #[test]
+    fn leaves_calls_of_other_crates() {
+        let guest = guest(
+            "use std::io;
+            fn main() {
+                let n: u32 = zk_rust_io::read();
+                let _ = io::read();
+                zk_rust_io::commit(&n);
+            }",
+        );
+        assert!(guest.contains("let n: u32 = vm_read();"), "{}", guest);
+        assert!(guest.contains("let _ = io::read();"), "{}", guest);
+    }
+
+    #[test]
+    fn rewrites_calls_in_helper_functions() {
+        let guest = guest(
+            "fn main() {
+                let n = read_n();
+                zk_rust_io::commit(&n);
+            }
+            fn read_n() -> u32 {
+                zk_rust_io::read::<u32>()
+            }",
+        );
+        assert!(guest.contains("vm_read::<u32>()"), "{}", guest);
+        assert!(!guest.contains("zk_rust_io"), "{}", guest);
+    }
+
+    #[test]
+    fn keeps_turbofish_when_rewriting() {
+        let program = Program::parse(
+            "fn main() {
+                let n = zk_rust_io::read::<u32>();
+                zk_rust_io::commit::<u32>(&n);
+            }",
+        )
+        .unwrap();
+        let native = program
+            .native_source("renamed", &[IoRewrite::call("read", "stdin.read")])
+            .unwrap();
+        assert!(native.contains("fn renamed()"), "{}", native);
+        assert!(
+            native.contains("let n = stdin.read::<u32>();"),
+            "{}",
+            native
+        );
+        assert!(
+            native.contains("zk_rust_io::commit::<u32>(&n);"),
+            "{}",
+            native
+        );
+    }
+
+    #[test]
+    fn rewrites_native_calls_across_the_file() {
+        let program = Program::parse(
+            "fn main() {
+                commit_n(1);
+            }
+
+            fn commit_n(n: u32) {
+                zk_rust_io::commit(&n);
+            }
+
+            fn input() {
+                zk_rust_io::write(&1u32);
+            }",
+        )
+        .unwrap();
+        let native = program
+            .native_source("renamed", &[IoRewrite::call("commit", "vm_commit")])
+            .unwrap();
+        assert!(native.contains("fn renamed()"), "{}", native);
+        assert!(native.contains("vm_commit(&n);"), "{}", native);
+        assert!(!native.contains("fn input()"), "{}", native);
+    }
+
+    #[test]
+    fn reads_types_of_turbofish_and_let_annotations() {
+        let program = Program::parse(
+            "use zk_rust_io as io;
+            fn main() {
+                let a: u32 = zk_rust_io::read();
+                let b = io::read::<Vec<u8>>();
+                let c: u64 = zk_rust_io::read::<u64>();
+                zk_rust_io::commit(&(a, b, c));
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            types(program.read_types("read").unwrap()),
+            ["u32", "Vec < u8 >", "u64"]
+        );
+    }
+
+    #[test]
+    fn rejects_unannotated_reads() {
+        let program = Program::parse(
+            "fn main() {
+                let n = zk_rust_io::read();
+                zk_rust_io::commit::<u32>(&n);
+            }",
+        )
+        .unwrap();
+        assert!(program.read_types("read").is_err());
+    }
+
+    #[test]
+    fn reads_of_helper_functions_in_call_order() {
+        let program = Program::parse(
+            "fn main() {
+                let a: u8 = zk_rust_io::read();
+                let (b, c) = (read_u32(), read_u32());
+                let d = read_pair();
+                zk_rust_io::commit(&(a, b, c, d));
+            }
+            fn read_u32() -> u32 {
+                zk_rust_io::read::<u32>()
+            }
+            fn read_pair() -> (u64, String) {
+                let x: u64 = zk_rust_io::read();
+                (x, zk_rust_io::read::<String>())
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            types(program.read_types("read").unwrap()),
+            ["u8", "u32", "u32", "u64", "String"]
+        );
+    }
+
+    fn read_error(source: &str) -> String {
+        Program::parse(source)
+            .unwrap()
+            .read_types("read")
+            .err()
+            .expect("reads should be rejected")
+            .to_string()
+    }
+
+    #[test]
+    fn rejects_reads_within_loops() {
+        let error = read_error(
+            "fn main() {
+                let times: u8 = zk_rust_io::read();
+                for _ in 0..times {
+                    let key: [u8; 32] = zk_rust_io::read();
+                }
+            }",
+        );
+        assert!(error.contains("within a loop"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_reads_of_helper_functions_called_within_loops() {
+        let error = read_error(
+            "fn main() {
+                let mut n = 0;
+                while n < 10 {
+                    n += read_u32();
+                }
+            }
+            fn read_u32() -> u32 {
+                zk_rust_io::read::<u32>()
+            }",
+        );
+        assert!(error.contains("within a loop"), "{}", error);
+        assert!(error.contains("through `read_u32()`"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_reads_within_closures_and_conditionals() {
+        let error = read_error(
+            "fn main() {
+                let read = || zk_rust_io::read::<u32>();
+                let n = read();
+            }",
+        );
+        assert!(error.contains("within a closure"), "{}", error);
+
+        let error = read_error(
+            "fn main() {
+                let flag: bool = zk_rust_io::read();
+                if flag {
+                    let n: u32 = zk_rust_io::read();
+                }
+            }",
+        );
+        assert!(error.contains("within a conditional"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_reads_of_functions_not_called_by_name() {
+        let error = read_error(
+            "struct Input;
+            impl Input {
+                fn read() -> u32 {
+                    zk_rust_io::read::<u32>()
+                }
+            }
+            fn main() {
+                let n = Input::read();
+            }",
+        );
+        assert!(error.contains("must be called from `main()`"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_reads_of_recursive_functions() {
+        let error = read_error(
+            "fn main() {
+                let sum = read_sum(3);
+            }
+            fn read_sum(n: u32) -> u32 {
+                let x: u32 = zk_rust_io::read();
+                x + read_sum(n - 1)
+            }",
+        );
+        assert!(error.contains("recursive"), "{}", error);
+    }
+
+    #[test]
+    fn commit_types_of_turbofish_and_bindings() {
+        let program = Program::parse(
+            "fn main() {
+                let n: u32 = zk_rust_io::read();
+                zk_rust_io::commit(&n);
+                zk_rust_io::commit::<bool>(&(n % 2 == 0));
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            types(program.commit_types("commit", "out").unwrap()),
+            ["u32", "bool"]
+        );
+    }
+
+    #[test]
+    fn commit_types_of_shadowed_bindings() {
+        let program = Program::parse(
+            "fn main() {
+                let n: u32 = zk_rust_io::read();
+                let (even, n): (bool, u64) = (n % 2 == 0, n as u64);
+                zk_rust_io::commit(&n);
+                {
+                    let even: String = n.to_string();
+                    zk_rust_io::commit(&even);
+                }
+                zk_rust_io::commit(&even);
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            types(program.commit_types("commit", "out").unwrap()),
+            ["u64", "String", "bool"]
+        );
+
+        // `n` is shadowed without a type, so the types read by `output()` are used
+        let program = Program::parse(
+            "fn main() {
+                let n: u32 = zk_rust_io::read();
+                zk_rust_io::commit(&n);
+                let n = n as u64;
+                zk_rust_io::commit(&n);
+            }
+
+            fn output() {
+                let (n, m): (u32, u64) = zk_rust_io::out();
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            types(program.commit_types("commit", "out").unwrap()),
+            ["u32", "u64"]
+        );
+    }
+
+    #[test]
+    fn commit_types_of_helper_functions() {
+        let program = Program::parse(
+            "fn main() {
+                let n: u32 = zk_rust_io::read();
+                commit_pair(&n, n % 2 == 0);
+            }
+
+            fn commit_pair(n: &u32, even: bool) {
+                zk_rust_io::commit(n);
+                zk_rust_io::commit(&even);
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            types(program.commit_types("commit", "out").unwrap()),
+            ["u32", "bool"]
+        );
+    }
+
+    #[test]
+    fn rejects_commits_within_loops() {
+        let program = Program::parse(
+            "fn main() {
+                for i in 0..10u32 {
+                    commit_u32(i);
+                }
+            }
+
+            fn commit_u32(n: u32) {
+                zk_rust_io::commit(&n);
+            }",
+        )
+        .unwrap();
+        let error = program
+            .commit_types("commit", "out")
+            .err()
+            .expect("commits should be rejected")
+            .to_string();
+        assert!(
+            error.contains("within a loop, through `commit_u32()`"),
+            "{}",
+            error
+        );
+    }
+}
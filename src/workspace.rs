@@ -0,0 +1,166 @@
+use fs2::FileExt;
+use log::{info, warn};
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    utils,
+    zkvm::{WorkspaceLayout, ZkVm},
+};
+
+/// Directory of the per-run workspaces, relative to the zkRust home directory
+pub const RUNS_DIR: &str = "runs";
+/// Directory of the cargo target directories shared by the runs of each zkVM, relative to the
+/// zkRust home directory
+pub const TARGET_DIR: &str = "target";
+
+/// Names of the build outputs copied into the run directory
+const HOST_BIN: &str = "host";
+const PROGRAM_ELF: &str = "program.elf";
+
+/// Copy of the workspace of a zkVM used by a single run, so concurrent runs do not overwrite each
+/// other's programs. The directory mirrors the zkRust home directory, so the paths of the
+/// `WorkspaceLayout` resolve against it, and is removed on drop unless kept.
+pub struct RunWorkspace {
+    id: String,
+    dir: PathBuf,
+    target_dir: PathBuf,
+    keep: bool,
+}
+
+impl RunWorkspace {
+    /// Copies the workspace and base files of `vm` from `home_dir` into a new run directory.
+    pub fn create(vm: &dyn ZkVm, home_dir: &Path, keep: bool) -> io::Result<Self> {
+        Self::create_for(vm.id(), vm.layout(), home_dir, keep)
+    }
+
+    /// Copies the workspace and base files of `layout`, and the shared host modules, from
+    /// `home_dir` into a new run directory named after `id`, for runs without a zkVM such as
+    /// native ones.
+    pub fn create_for(
+        id: &str,
+        layout: &WorkspaceLayout,
+        home_dir: &Path,
+        keep: bool,
+    ) -> io::Result<Self> {
+        let target_dir = home_dir.join(TARGET_DIR).join(id);
+        let id = format!(
+            "{}_{}_{}",
+            id,
+            chrono::Local::now().format("%Y%m%d_%H%M%S_%3f"),
+            std::process::id()
+        );
+        let dir = home_dir.join(RUNS_DIR).join(&id);
+        fs::create_dir_all(&target_dir)?;
+
+        let run = Self {
+            id,
+            dir,
+            target_dir,
+            keep,
+        };
+        copy_workspace(&home_dir.join(layout.root), &run.dir.join(layout.root))?;
+        copy_workspace(
+            &home_dir.join(layout.base_dir),
+            &run.dir.join(layout.base_dir),
+        )?;
+        for module in utils::SHARED_HOST_MODULES {
+            let path = Path::new(utils::BASE_FILES_DIR).join(format!("{}.rs", module));
+            fs::copy(home_dir.join(&path), run.dir.join(&path))?;
+        }
+        info!(
+            "Created workspace for run {} in: {}",
+            run.id,
+            run.dir.display()
+        );
+        Ok(run)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Root of the run, the `WorkspaceLayout` paths are relative to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cargo target directory shared with the other runs of the same zkVM.
+    pub fn target_dir(&self) -> &Path {
+        &self.target_dir
+    }
+
+    /// Host binary copied out of the shared target directory by `take_build_outputs`.
+    pub fn host_bin(&self) -> PathBuf {
+        self.dir.join(HOST_BIN)
+    }
+
+    /// Guest ELF copied out of the shared target directory by `take_build_outputs`.
+    pub fn program_elf(&self) -> PathBuf {
+        self.dir.join(PROGRAM_ELF)
+    }
+
+    /// Takes an exclusive lock on the shared target directory, released when the returned file
+    /// is dropped.
+    pub fn lock_target(&self) -> io::Result<File> {
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.target_dir.with_extension("lock"))?;
+        lock.lock_exclusive()?;
+        Ok(lock)
+    }
+
+    /// Copies the host binary and guest ELF of `vm` out of the shared target directory, so later
+    /// builds of other runs do not replace them. Must be called while holding the target lock.
+    pub fn take_build_outputs(&self, vm: &dyn ZkVm) -> io::Result<()> {
+        let layout = vm.layout();
+        fs::copy(self.target_dir.join(layout.host_bin), self.host_bin())?;
+        // The ELF is embedded in the host binary, the copy is only kept for reporting its size
+        if let Some(program_elf) = layout.program_elf {
+            let program_elf = self.target_dir.join(program_elf);
+            if let Err(e) = fs::copy(&program_elf, self.program_elf()) {
+                warn!("Failed to copy {}: {}", program_elf.display(), e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RunWorkspace {
+    fn drop(&mut self) {
+        if self.keep {
+            info!(
+                "Workspace of run {} kept in: {}",
+                self.id,
+                self.dir.display()
+            );
+            return;
+        }
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            warn!("Failed to remove workspace {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+/// Copies a workspace directory, skipping build outputs.
+pub fn copy_workspace(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.file_name() == "target" {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_workspace(&entry.path(), &dst.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), dst.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
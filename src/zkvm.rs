@@ -0,0 +1,358 @@
+use aligned_sdk::core::types::ProvingSystemId;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::Duration,
+};
+
+#[cfg(feature = "nexus")]
+use crate::nexus::NexusMetrics;
+#[cfg(feature = "openvm")]
+use crate::openvm::OpenVmMetrics;
+use crate::{
+    error::Result,
+    evm::EvmExport,
+    input::ProgramInput,
+    jolt::JoltMetrics,
+    output::OutputEncoding,
+    precompiles::PrecompilePatch,
+    risc0::Risc0Metrics,
+    sp1::SP1Metrics,
+    telemetry::TelemetryCollector,
+    transform::{IoRewrite, Program},
+    utils,
+};
+
+/// Paths of a zkVM workspace, relative to the zkRust home directory (`~/.zkRust`) or the
+/// directory of a run mirroring it.
+pub struct WorkspaceLayout {
+    /// Directory holding the guest and host of the zkVM, copied for each run.
+    pub root: &'static str,
+    /// Directory holding the templates of the zkVM, copied for each run.
+    pub base_dir: &'static str,
+    /// Directory `cargo build` is invoked from and the host is run in.
+    pub workspace_dir: &'static str,
+    pub guest_dir: &'static str,
+    pub guest_main: &'static str,
+    pub guest_cargo_toml: &'static str,
+    pub host_dir: &'static str,
+    pub host_main: &'static str,
+    pub host_cargo_toml: &'static str,
+    pub base_guest_cargo_toml: &'static str,
+    pub base_host_cargo_toml: &'static str,
+    pub base_host: &'static str,
+    /// Compiled guest program, relative to the cargo target directory. `None` for zkVMs whose
+    /// host compiles the guest when it runs (Jolt).
+    pub program_elf: Option<&'static str>,
+    /// Compiled host program, relative to the cargo target directory.
+    pub host_bin: &'static str,
+}
+
+/// Files written by the host, relative to the proof data directory.
+pub struct Artifacts {
+    /// Directory the host saves the artifacts to.
+    pub dir: &'static str,
+    pub proof: &'static str,
+    /// ELF of the program (SP1, Jolt) or its image ID (RISC0).
+    pub program: &'static str,
+    pub public_input: &'static str,
+    pub metrics: &'static str,
+    /// Report written by the host when only running the executor.
+    pub execution_report: &'static str,
+    /// Calldata of the proof for the EVM verifier contracts, written for wrapped proofs.
+    pub calldata: &'static str,
+    /// Types of the values committed by the program, recorded by zkRust.
+    pub commit_types: &'static str,
+    /// Public values decoded with the commit types, written by zkRust.
+    pub output: &'static str,
+}
+
+/// Locations of the artifacts of a zkVM within a proof data directory.
+#[derive(Debug, Clone)]
+pub struct ArtifactPaths {
+    pub dir: PathBuf,
+    pub proof: PathBuf,
+    pub program: PathBuf,
+    pub public_input: PathBuf,
+    pub metrics: PathBuf,
+    pub execution_report: PathBuf,
+    pub calldata: PathBuf,
+    pub commit_types: PathBuf,
+    pub output: PathBuf,
+}
+
+impl Artifacts {
+    pub fn paths(&self, proof_data_dir: &Path) -> ArtifactPaths {
+        let dir = proof_data_dir.join(self.dir);
+        ArtifactPaths {
+            proof: dir.join(self.proof),
+            program: dir.join(self.program),
+            public_input: dir.join(self.public_input),
+            metrics: dir.join(self.metrics),
+            execution_report: dir.join(self.execution_report),
+            calldata: dir.join(self.calldata),
+            commit_types: dir.join(self.commit_types),
+            output: dir.join(self.output),
+            dir,
+        }
+    }
+}
+
+/// Argument passing the host to only run the executor, without generating proofs.
+pub const HOST_EXECUTE_MODE: &str = "execute";
+
+/// Arguments of the host after its mode: the input file and its format, if given.
+pub fn host_input_args(input: Option<&ProgramInput>) -> Vec<OsString> {
+    input
+        .map(|input| vec![input.path.clone().into(), input.format.as_str().into()])
+        .unwrap_or_default()
+}
+
+/// Stage of the proof saved by the host. Each mode also generates the stages before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofMode {
+    /// Proof of each segment of the execution.
+    Core,
+    /// Core proof recursively compressed into a single STARK.
+    #[default]
+    Compressed,
+    /// Compressed proof wrapped in a Groth16 SNARK, verifiable on the EVM.
+    Groth16,
+    /// Compressed proof wrapped in a PLONK SNARK, verifiable on the EVM.
+    Plonk,
+}
+
+impl ProofMode {
+    /// Argument passing the proof mode to the host.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProofMode::Core => "core",
+            ProofMode::Compressed => "compressed",
+            ProofMode::Groth16 => "groth16",
+            ProofMode::Plonk => "plonk",
+        }
+    }
+
+    /// Whether the proof is wrapped in a SNARK verifiable by the EVM verifier contracts.
+    pub fn is_wrapped(self) -> bool {
+        matches!(self, ProofMode::Groth16 | ProofMode::Plonk)
+    }
+}
+
+impl std::fmt::Display for ProofMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options of the prover run by the host.
+#[derive(Debug, Clone, Default)]
+pub struct ProverOptions {
+    pub proof_mode: ProofMode,
+    pub use_gpu: bool,
+    /// Generates mock proofs, which are not checked by the verifiers, without paying the proving
+    /// cost.
+    pub mock: bool,
+    /// Values written to the guest instead of the ones written by `input()`.
+    pub input: Option<ProgramInput>,
+}
+
+/// Invocations of a syscall by the guest, written by the host.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyscallStats {
+    pub count: u64,
+    /// Cycles spent in the syscall, `None` for zkVMs not reporting them (SP1).
+    pub cycles: Option<u64>,
+}
+
+/// Cycle and segment counts of an execution of the program, written by the host.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub execution_duration: Duration,
+    /// Syscalls invoked by the guest, by name.
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    /// Syscalls accelerated by a precompile, among `syscalls`.
+    #[serde(default)]
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+/// Metrics written by the host of each zkVM.
+pub enum Metrics {
+    Sp1(SP1Metrics),
+    Risc0(Risc0Metrics),
+    Jolt(JoltMetrics),
+    #[cfg(feature = "openvm")]
+    OpenVm(OpenVmMetrics),
+    #[cfg(feature = "nexus")]
+    Nexus(NexusMetrics),
+}
+
+impl Metrics {
+    pub fn record(&self, telemetry: &TelemetryCollector) {
+        match self {
+            Metrics::Sp1(metrics) => metrics.record(telemetry),
+            Metrics::Risc0(metrics) => metrics.record(telemetry),
+            Metrics::Jolt(metrics) => metrics.record(telemetry),
+            #[cfg(feature = "openvm")]
+            Metrics::OpenVm(metrics) => metrics.record(telemetry),
+            #[cfg(feature = "nexus")]
+            Metrics::Nexus(metrics) => metrics.record(telemetry),
+        }
+    }
+
+    pub fn proof_mode(&self) -> ProofMode {
+        match self {
+            Metrics::Sp1(metrics) => metrics.proof_mode,
+            Metrics::Risc0(metrics) => metrics.proof_mode,
+            Metrics::Jolt(metrics) => metrics.proof_mode,
+            #[cfg(feature = "openvm")]
+            Metrics::OpenVm(metrics) => metrics.proof_mode,
+            #[cfg(feature = "nexus")]
+            Metrics::Nexus(metrics) => metrics.proof_mode,
+        }
+    }
+
+    /// Whether the host generated a mock proof.
+    pub fn is_mock(&self) -> bool {
+        match self {
+            Metrics::Sp1(metrics) => metrics.mock,
+            Metrics::Risc0(metrics) => metrics.mock,
+            Metrics::Jolt(metrics) => metrics.mock,
+            #[cfg(feature = "openvm")]
+            Metrics::OpenVm(metrics) => metrics.mock,
+            #[cfg(feature = "nexus")]
+            Metrics::Nexus(metrics) => metrics.mock,
+        }
+    }
+}
+
+/// A zkVM zkRust can compile programs for and generate proofs of their execution with.
+pub trait ZkVm: Send + Sync {
+    /// Name of the zkVM used in logs and telemetry, e.g. `SP1`.
+    fn name(&self) -> &'static str;
+
+    /// Lowercase identifier of the zkVM used in file names, e.g. `sp1`.
+    fn id(&self) -> &'static str;
+
+    fn layout(&self) -> &'static WorkspaceLayout;
+
+    /// Header added to programs for generating proofs of their execution.
+    fn guest_header(&self) -> &'static str;
+
+    /// Rewriting of `zk_rust_io::read` and `zk_rust_io::commit` within the guest.
+    fn guest_io(&self) -> Vec<IoRewrite<'static>>;
+
+    /// Writes the host program, inserting the `input()` and `output()` bodies of `program`.
+    fn prepare_host(&self, program: &Program, root_dir: &Path) -> Result<()>;
+
+    /// Forks of the crates accelerated by the precompiles of the zkVM, patched into the guest
+    /// `Cargo.toml` for the versions it locks when precompiles are enabled.
+    fn precompile_patches(&self) -> &'static [PrecompilePatch];
+
+    /// Crates of the zkVM whose versions in the guest and host manifests are kept when merging
+    /// the manifest of a program.
+    fn pinned_crates(&self) -> &'static [&'static str];
+
+    /// Builds the guest and host programs into `target_dir`, with GPU support if `use_gpu` is set.
+    fn build(
+        &self,
+        workspace_dir: &Path,
+        target_dir: &Path,
+        use_gpu: bool,
+    ) -> io::Result<ExitStatus>;
+
+    /// Runs the host binary in `workspace_dir`, generating and verifying a proof and saving its
+    /// artifacts to `output_dir`.
+    fn prove(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        options: &ProverOptions,
+    ) -> io::Result<ExitStatus>;
+
+    /// Proof modes the host can generate.
+    fn proof_modes(&self) -> &'static [ProofMode];
+
+    /// Whether the host can generate mock proofs.
+    fn supports_mock(&self) -> bool {
+        true
+    }
+
+    /// Whether programs can have a `src/lib.rs` library next to their `main.rs`.
+    fn supports_lib(&self) -> bool {
+        true
+    }
+
+    /// Runs the host binary in `workspace_dir`, executing the program without generating a proof
+    /// and saving an execution report to `output_dir`.
+    fn execute(
+        &self,
+        host_bin: &Path,
+        workspace_dir: &Path,
+        output_dir: &Path,
+        input: Option<&ProgramInput>,
+    ) -> io::Result<ExitStatus>;
+
+    /// Verifies a saved proof against the program it was generated for, the ELF (SP1) or its
+    /// image ID (RISC0), and its public values against `pub_input_path` if given.
+    fn verify(
+        &self,
+        proof_path: &Path,
+        program_path: &Path,
+        pub_input_path: Option<&Path>,
+    ) -> Result<()>;
+
+    fn artifacts(&self) -> &'static Artifacts;
+
+    /// Serialization of the committed values in the public values of the proofs.
+    fn output_encoding(&self) -> OutputEncoding;
+
+    /// Foundry project checking the wrapped proof in `artifacts` with the EVM verifier contracts
+    /// of the zkVM.
+    fn export_evm(&self, artifacts: &ArtifactPaths, proof_mode: ProofMode) -> Result<EvmExport>;
+
+    /// Proving system the proofs are submitted to Aligned as, `None` if Aligned cannot verify
+    /// them.
+    fn proving_system_id(&self) -> Option<ProvingSystemId>;
+
+    fn read_metrics(&self, metrics_path: &Path) -> io::Result<Metrics>;
+
+    fn read_execution_report(&self, report_path: &Path) -> io::Result<ExecutionReport> {
+        let report = fs::read_to_string(report_path)?;
+        serde_json::from_str(&report).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn prepare_guest(&self, program: &Program, root_dir: &Path) -> Result<()> {
+        utils::prepare_guest(
+            program,
+            self.guest_header(),
+            &self.guest_io(),
+            &root_dir.join(self.layout().guest_main),
+        )
+    }
+
+    fn prepare_workspace(&self, guest_path: &Path, root_dir: &Path) -> io::Result<()> {
+        let layout = self.layout();
+        utils::prepare_workspace(
+            guest_path,
+            &root_dir.join(layout.guest_dir),
+            &root_dir.join(layout.guest_cargo_toml),
+            &root_dir.join(layout.host_dir),
+            &root_dir.join(layout.host_cargo_toml),
+            &root_dir.join(layout.base_host_cargo_toml),
+            &root_dir.join(layout.base_guest_cargo_toml),
+            &root_dir.join(utils::BASE_FILES_DIR),
+            self.pinned_crates(),
+        )
+    }
+}
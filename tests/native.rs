@@ -0,0 +1,73 @@
+//! Native runs of `examples/fibonacci`, building the native crate from the workspaces and the
+//! `zk_rust_io` crate of this repository as they are installed in `~/.zkRust`.
+
+use std::{fs, io, path::Path};
+use zkRust::{native, SystemArg};
+
+/// Values committed by `examples/fibonacci` for the `n = 1000` written by its `input()`.
+const FIBONACCI_COMMITS: [u32; 3] = [1000, 5965, 3651];
+
+fn copy_dir(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dst.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), dst.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// zkRust home with the native workspace installed. Kept between runs, so the shared target
+/// directory is reused.
+fn native_home() -> io::Result<std::path::PathBuf> {
+    let repo = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let home = std::env::temp_dir().join("zkrust_native_test");
+    for dir in [
+        native::NATIVE_WORKSPACE_ROOT,
+        native::NATIVE_BASE_DIR,
+        native::NATIVE_IO_CRATE_DIR,
+    ] {
+        let _ = fs::remove_dir_all(home.join(dir));
+        copy_dir(&repo.join(dir), &home.join(dir))?;
+    }
+    fs::copy(
+        repo.join("workspaces/base_files/inputs.rs"),
+        home.join("workspaces/base_files/inputs.rs"),
+    )?;
+    Ok(home)
+}
+
+#[test]
+fn fibonacci_commits_in_the_encoding_of_each_zkvm() {
+    let home = native_home().unwrap();
+    let proof_data_dir = home.join("proof_data");
+    let output = native::run(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/fibonacci"),
+        None,
+        &home,
+        &proof_data_dir,
+        false,
+    )
+    .unwrap();
+
+    let bincode: Vec<u8> = FIBONACCI_COMMITS
+        .iter()
+        .flat_map(|value| bincode::serialize(value).unwrap())
+        .collect();
+    assert_eq!(
+        output.public_values(SystemArg::Sp1.zkvm()).unwrap(),
+        bincode
+    );
+    // Each `u32` is committed to the RISC0 journal as one little-endian word
+    let words: Vec<u8> = FIBONACCI_COMMITS
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    assert_eq!(
+        output.public_values(SystemArg::Risc0.zkvm()).unwrap(),
+        words
+    );
+}
@@ -0,0 +1,174 @@
+//! Regression tests for decoding several values committed by a RISC0 guest, as
+//! `examples/fibonacci` does, in the same way as SP1. The test proving `examples/fibonacci`
+//! requires the RISC0 and SP1 toolchains and the zkRust workspaces in `~/.zkRust`, run it with
+//! `cargo test -- --ignored`.
+
+#[path = "../workspaces/risc0/host/src/journal.rs"]
+mod journal;
+
+use journal::JournalReader;
+use risc0_zkvm::{serde::to_vec, Journal};
+use serde::Serialize;
+use std::{collections::BTreeMap, path::Path};
+use zkRust::{
+    output::{self, OutputEncoding},
+    pipeline::{self, ProveConfig},
+    risc0::RISC0_IO_OUT,
+    sp1::SP1_HOST_READ,
+    transform::{IoRewrite, Program},
+    utils, SystemArg,
+};
+
+/// Values committed by `examples/fibonacci` for the `n = 1000` written by its `input()`.
+const FIBONACCI_COMMITS: [u32; 3] = [1000, 5965, 3651];
+
+fn fibonacci() -> Program {
+    Program::from_file(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/fibonacci/src/main.rs"),
+    )
+    .unwrap()
+}
+
+/// Journal of the fibonacci guest, with each value committed separately.
+fn fibonacci_journal() -> Journal {
+    let bytes = FIBONACCI_COMMITS
+        .iter()
+        .flat_map(|value| to_vec(value).unwrap())
+        .flat_map(u32::to_le_bytes)
+        .collect();
+    Journal::new(bytes)
+}
+
+#[test]
+fn journal_reader_reads_each_commit_in_order() {
+    let mut journal = JournalReader::new(&fibonacci_journal());
+    let values: Vec<u32> = (0..3).map(|_| journal.read()).collect();
+    assert_eq!(values, FIBONACCI_COMMITS);
+}
+
+#[test]
+fn journal_reader_reads_commits_as_tuple() {
+    let mut journal = JournalReader::new(&fibonacci_journal());
+    let (n, a, b): (u32, u32, u32) = journal.read();
+    assert_eq!([n, a, b], FIBONACCI_COMMITS);
+}
+
+#[test]
+fn output_reads_stream_on_both_backends() {
+    let program = fibonacci();
+    let risc0 = program
+        .output_body(&[IoRewrite::call(utils::IO_OUT, RISC0_IO_OUT)])
+        .unwrap();
+    let sp1 = program
+        .output_body(&[IoRewrite::call(utils::IO_OUT, SP1_HOST_READ)])
+        .unwrap();
+    assert!(risc0.contains("journal.read()"));
+    assert_eq!(risc0.replace("journal.read()", "public_values.read()"), sp1);
+}
+
+#[test]
+fn decoded_output_matches_on_both_backends() {
+    let types = output::commit_types(&fibonacci()).unwrap();
+    let sp1_public_values: Vec<u8> = FIBONACCI_COMMITS
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+
+    let risc0 = output::decode(&types, &fibonacci_journal().bytes, OutputEncoding::Risc0).unwrap();
+    let sp1 = output::decode(&types, &sp1_public_values, OutputEncoding::Bincode).unwrap();
+    assert_eq!(risc0, sp1);
+    let values: Vec<_> = risc0
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["value"].as_u64().unwrap() as u32)
+        .collect();
+    assert_eq!(values, FIBONACCI_COMMITS);
+}
+
+/// Output of `examples/fibonacci` decoded from the public values of a mock proof of `system`.
+fn prove_fibonacci(system: SystemArg) -> serde_json::Value {
+    let proof_data_dir = std::env::temp_dir().join(format!("zkrust_fibonacci_{:?}", system));
+    let mut config = ProveConfig::new(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/fibonacci"),
+        system,
+    );
+    config.mock = true;
+    config.proof_data_dir = proof_data_dir.clone();
+
+    let outcome = pipeline::prove(&config).unwrap();
+    let _ = std::fs::remove_dir_all(proof_data_dir);
+    outcome.output.expect("output of the proof not decoded")
+}
+
+#[test]
+#[ignore = "requires the RISC0 and SP1 toolchains"]
+fn fibonacci_guest_output_matches_on_both_backends() {
+    let risc0 = prove_fibonacci(SystemArg::Risc0);
+    let values: Vec<_> = risc0
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["value"].as_u64().unwrap() as u32)
+        .collect();
+    assert_eq!(values, FIBONACCI_COMMITS);
+    assert_eq!(risc0, prove_fibonacci(SystemArg::Sp1));
+}
+
+#[derive(Serialize)]
+struct Account {
+    name: String,
+    balance: u64,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+enum Event {
+    Opened,
+    Deposit(u64),
+    Transfer { to: [u8; 4], amount: i128 },
+}
+
+/// Commits `value` natively, adding its RISC0 serialization to `expected`.
+fn commit<T: Serialize>(value: &T, expected: &mut Vec<u32>) {
+    zk_rust_io::commit(value);
+    expected.extend(to_vec(value).unwrap());
+}
+
+#[test]
+fn native_commits_are_encoded_like_the_risc0_journal() {
+    zk_rust_io::reset();
+    let mut expected = Vec::new();
+    commit(&(true, 7u8, -3i16, u32::MAX), &mut expected);
+    commit(&(-1i64, u64::MAX - 1, 1u128 << 100), &mut expected);
+    commit(&(1.5f32, -2.25f64, 'ž'), &mut expected);
+    commit(&"odd length".to_string(), &mut expected);
+    commit(&vec![1u8, 2, 3, 4, 5], &mut expected);
+    commit(&(Some(3u16), None::<u64>, ()), &mut expected);
+    commit(
+        &Account {
+            name: "alice".to_string(),
+            balance: 100,
+            tags: vec!["a".to_string(), "bc".to_string()],
+        },
+        &mut expected,
+    );
+    commit(
+        &[
+            Event::Opened,
+            Event::Deposit(5),
+            Event::Transfer {
+                to: [1, 2, 3, 4],
+                amount: -7,
+            },
+        ],
+        &mut expected,
+    );
+    commit(&BTreeMap::from([(1u32, "x"), (2, "yz")]), &mut expected);
+
+    let expected: Vec<u8> = expected
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+    assert_eq!(zk_rust_io::risc0_public_values(), expected);
+}
@@ -0,0 +1,57 @@
+//! Wrapping of SP1 proofs with the mock prover of the SDK, checking the wrapped proof and the
+//! arguments of the EVM verifier call are saved. Requires the SP1 toolchain and the zkRust
+//! workspaces in `~/.zkRust`, run with `cargo test -- --ignored`.
+
+use std::path::Path;
+use zkRust::{
+    pipeline::{self, ProveConfig},
+    zkvm::{Metrics, ProofMode},
+    SystemArg,
+};
+
+fn prove_mock(proof_mode: ProofMode) {
+    let proof_data_dir = std::env::temp_dir().join(format!("zkrust_sp1_{}", proof_mode));
+    let mut config = ProveConfig::new(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/fibonacci"),
+        SystemArg::Sp1,
+    );
+    config.proof_mode = proof_mode;
+    config.mock = true;
+    config.proof_data_dir = proof_data_dir.clone();
+
+    let outcome = pipeline::prove(&config).unwrap();
+
+    let Some(Metrics::Sp1(metrics)) = &outcome.metrics else {
+        panic!("SP1 metrics not saved");
+    };
+    assert!(metrics.mock);
+    assert_eq!(metrics.proof_mode, proof_mode);
+    assert!(outcome.artifacts.proof.exists());
+
+    let calldata: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&outcome.artifacts.calldata).unwrap()).unwrap();
+    for key in ["program_vkey", "public_values", "proof_bytes"] {
+        assert!(
+            calldata[key]
+                .as_str()
+                .is_some_and(|value| value.starts_with("0x")),
+            "{} missing from {}",
+            key,
+            calldata
+        );
+    }
+
+    let _ = std::fs::remove_dir_all(proof_data_dir);
+}
+
+#[test]
+#[ignore = "requires the SP1 toolchain"]
+fn mock_groth16_proof_saves_wrapped_proof_and_calldata() {
+    prove_mock(ProofMode::Groth16);
+}
+
+#[test]
+#[ignore = "requires the SP1 toolchain"]
+fn mock_plonk_proof_saves_wrapped_proof_and_calldata() {
+    prove_mock(ProofMode::Plonk);
+}
@@ -0,0 +1,58 @@
+use serde::{de::DeserializeOwned, Deserialize};
+use std::io::Cursor;
+
+/// Values read by the guest, loaded from the file given to zkRust with `--input`.
+pub enum Inputs {
+    /// Elements of a JSON array or of the `inputs` array of a TOML file.
+    Values(std::vec::IntoIter<serde_json::Value>),
+    /// Concatenated bincode-serialized values.
+    Bincode(Cursor<Vec<u8>>),
+}
+
+#[derive(Deserialize)]
+struct TomlInputs {
+    inputs: Vec<serde_json::Value>,
+}
+
+impl Inputs {
+    pub fn open(path: &str, format: &str) -> Self {
+        let contents = std::fs::read(path).expect("Failed to read input file");
+        match format {
+            "json" => Inputs::Values(
+                serde_json::from_slice::<Vec<serde_json::Value>>(&contents)
+                    .expect("Input file must hold a JSON array")
+                    .into_iter(),
+            ),
+            "toml" => {
+                let contents = String::from_utf8(contents).expect("Input file must be UTF-8");
+                Inputs::Values(
+                    toml::from_str::<TomlInputs>(&contents)
+                        .expect("Input file must hold an `inputs` array")
+                        .inputs
+                        .into_iter(),
+                )
+            }
+            "bincode" => Inputs::Bincode(Cursor::new(contents)),
+            "hex" => {
+                let contents = String::from_utf8(contents).expect("Input file must be UTF-8");
+                let bytes = hex::decode(contents.trim().trim_start_matches("0x"))
+                    .expect("Input file must be hex encoded");
+                Inputs::Bincode(Cursor::new(bytes))
+            }
+            format => panic!("Unknown input format: {}", format),
+        }
+    }
+
+    /// Deserializes the next value, read by the guest as a `T`.
+    pub fn next<T: DeserializeOwned>(&mut self) -> T {
+        match self {
+            Inputs::Values(values) => {
+                let value = values.next().expect("Input file holds too few values");
+                serde_json::from_value(value).expect("Failed to deserialize input value")
+            }
+            Inputs::Bincode(bytes) => {
+                bincode::deserialize_from(bytes).expect("Failed to deserialize input value")
+            }
+        }
+    }
+}
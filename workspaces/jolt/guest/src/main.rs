@@ -0,0 +1,47 @@
+pub mod zkrust {
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::{cell::RefCell, io::Cursor};
+    thread_local! {
+        static INPUT : RefCell < Cursor < Vec < u8 >>> =
+        RefCell::new(Cursor::new(Vec::new())); static OUTPUT : RefCell < Vec < u8 >> =
+        RefCell::new(Vec::new());
+    }
+    #[jolt::provable(max_input_size = 65536, max_output_size = 65536)]
+    pub fn method(input: Vec<u8>) -> Vec<u8> {
+        INPUT.with(|cell| *cell.borrow_mut() = Cursor::new(input));
+        OUTPUT.with(|cell| cell.borrow_mut().clear());
+        super::main();
+        OUTPUT.with(|cell| cell.take())
+    }
+    pub fn read<T: DeserializeOwned>() -> T {
+        INPUT
+            .with(|cell| {
+                bincode::deserialize_from(&mut *cell.borrow_mut())
+                    .expect("Failed to read input")
+            })
+    }
+    pub fn commit<T: Serialize>(value: &T) {
+        OUTPUT
+            .with(|cell| {
+                bincode::serialize_into(&mut *cell.borrow_mut(), value)
+                    .expect("Failed to commit")
+            })
+    }
+}
+use zk_rust_io;
+pub fn main() {
+    println!("cycle-tracker-report-start: {}", env!("CARGO_PKG_NAME"));
+    let n: u32 = crate::zkrust::read();
+    crate::zkrust::commit(&n);
+    let mut a: u32 = 0;
+    let mut b: u32 = 1;
+    for _ in 0..n {
+        let mut c = a + b;
+        c %= 7919;
+        a = b;
+        b = c;
+    }
+    crate::zkrust::commit(&a);
+    crate::zkrust::commit(&b);
+    println!("cycle-tracker-report-end: {}", env!("CARGO_PKG_NAME"));
+}
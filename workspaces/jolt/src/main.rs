@@ -0,0 +1,160 @@
+use jolt_sdk::Serializable;
+use serde::{de::DeserializeOwned, Serialize};
+mod inputs;
+mod metrics;
+use metrics::{ExecutionReport, JoltMetrics, MetricsCollector};
+use tracing::{error, info};
+
+/// Target directory the host compiles the guest to, relative to the workspace.
+const GUEST_TARGET_DIR: &str = "target/jolt-guest";
+
+/// Values read by the guest, serialized with bincode and passed as the input of its entrypoint.
+#[derive(Default, Clone)]
+pub struct JoltStdin {
+    pub bytes: Vec<u8>,
+}
+
+impl JoltStdin {
+    pub fn write<T: Serialize>(&mut self, value: &T) {
+        bincode::serialize_into(&mut self.bytes, value).expect("Failed to serialize input");
+    }
+}
+
+/// Values committed by the guest, returned by its entrypoint.
+pub struct PublicValues {
+    cursor: std::io::Cursor<Vec<u8>>,
+}
+
+impl PublicValues {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            cursor: std::io::Cursor::new(bytes),
+        }
+    }
+
+    /// Deserializes the next committed value.
+    pub fn read<T: DeserializeOwned>(&mut self) -> T {
+        bincode::deserialize_from(&mut self.cursor).expect("Failed to read public values")
+    }
+}
+
+/// Exit code of the host when a proof it generated fails verification, distinct from the exit
+/// code of panics.
+const VERIFICATION_FAILED: i32 = 103;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // Directory the proof artifacts are saved to
+    let output_dir = std::path::PathBuf::from(args[1].clone());
+    // Only run the tracer with `execute`, otherwise Jolt only generates `compressed` proofs
+    let mode = args.get(2).map_or("compressed", String::as_str);
+    let execute_only = mode == "execute";
+    // Setup the logger.
+    tracing_subscriber::fmt::init();
+
+    let mut metrics = JoltMetrics::default();
+    let mut core_timer = MetricsCollector::new();
+
+    // Setup the inputs and set as mutable to allow for template code to access it if needed
+    let mut stdin = JoltStdin::default();
+    match args.get(3) {
+        // Values given to zkRust with `--input`, instead of the ones written by `input()`
+        Some(input_path) => {
+            let mut inputs = inputs::Inputs::open(input_path, &args[4]);
+            read_inputs(&mut inputs, &mut stdin);
+        }
+        None => input(&mut stdin),
+    }
+
+    // First trace the program to get cycle count
+    let mut execution_timer = MetricsCollector::new();
+    execution_timer.start_timing();
+    let summary = method::zkrust::analyze_method(stdin.bytes.clone());
+    let execution_duration = execution_timer.elapsed().unwrap();
+    metrics.cycles = summary.trace_len() as u64;
+    // Jolt proves the whole trace at once
+    metrics.num_segments = 1;
+
+    if execute_only {
+        // Public values of the program, run natively
+        let mut public_values = PublicValues::new(method::zkrust::method(stdin.bytes.clone()));
+        output(&mut public_values);
+
+        let report = ExecutionReport {
+            cycles: metrics.cycles,
+            num_segments: metrics.num_segments,
+            execution_duration,
+            syscalls: metrics.syscalls.clone(),
+            precompiles: metrics.precompiles.clone(),
+        };
+        std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+        metrics::write_execution_report(&report, &output_dir)
+            .expect("Failed to save execution report");
+        return;
+    }
+
+    let mut program = method::zkrust::compile_method(GUEST_TARGET_DIR);
+    let elf = program
+        .get_elf_contents()
+        .expect("Failed to read the ELF of the guest");
+    let prover_preprocessing = method::zkrust::preprocess_prover_method(&mut program);
+    let verifier_preprocessing = method::zkrust::preprocess_verifier_method(&mut program);
+    let prove_method = method::zkrust::build_prover_method(program, prover_preprocessing);
+    let verify_method = method::zkrust::build_verifier_method(verifier_preprocessing);
+    metrics.proof_mode = mode.to_string();
+
+    // Generate proof
+    core_timer.start_timing();
+    let (public_values, proof) = prove_method(stdin.bytes.clone());
+    metrics.core_prove_duration = core_timer.elapsed().unwrap();
+    metrics.core_proof_size = proof.size().expect("Failed to compute Jolt proof size");
+    metrics.recursive_proof_size = metrics.core_proof_size;
+
+    // Verify proof
+    core_timer.start_timing();
+    let is_valid = verify_method(stdin.bytes.clone(), public_values.clone(), proof.clone());
+    check_verified(is_valid.then_some(()).ok_or("invalid proof"), "Jolt");
+    metrics.core_verify_duration = core_timer.elapsed().unwrap();
+
+    std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+
+    // Save proof artifacts
+    proof
+        .save_to_file(output_dir.join("jolt.proof"))
+        .expect("Failed to save Jolt Proof file");
+    std::fs::write(output_dir.join("jolt.elf"), elf).expect("Failed to create Jolt elf file");
+    std::fs::write(output_dir.join("jolt.pub"), &public_values)
+        .expect("Failed to save Jolt public input");
+
+    output(&mut PublicValues::new(public_values));
+
+    // Save metrics
+    info!("Attempting to save metrics...");
+    match metrics::write_metrics(&metrics, &output_dir) {
+        Ok(_) => info!("Successfully saved metrics"),
+        Err(e) => error!("Failed to save metrics: {}", e),
+    };
+}
+
+#[allow(unused_variables)]
+fn input(stdin: &mut JoltStdin) {
+    // INPUT //
+}
+
+#[allow(unused_variables)]
+fn read_inputs(inputs: &mut inputs::Inputs, stdin: &mut JoltStdin) {
+    // READ INPUTS //
+}
+
+#[allow(unused_variables)]
+fn output(public_values: &mut PublicValues) {
+    // OUTPUT //
+}
+
+/// Exits with `VERIFICATION_FAILED` if verifying the `stage` proof failed.
+fn check_verified<T, E: std::fmt::Debug>(result: Result<T, E>, stage: &str) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Failed to verify {} proof: {:?}", stage, e);
+        std::process::exit(VERIFICATION_FAILED)
+    })
+}
@@ -0,0 +1,50 @@
+mod inputs;
+
+/// Public values committed by the program, in the encoding of the SP1, Jolt and Nexus public
+/// values, and in the word-padded encoding of the RISC0 and OpenVM journals.
+const BINCODE_PUBLIC_VALUES: &str = "native_bincode.pub";
+const RISC0_PUBLIC_VALUES: &str = "native_risc0.pub";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // Directory the public values are saved to
+    let output_dir = std::path::PathBuf::from(args[1].clone());
+
+    match args.get(2) {
+        // Values given to zkRust with `--input`, instead of the ones written by `input()`
+        Some(input_path) => {
+            let mut inputs = inputs::Inputs::open(input_path, &args[3]);
+            read_inputs(&mut inputs);
+        }
+        None => input(),
+    }
+
+    __zkrust_main();
+
+    std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+    std::fs::write(
+        output_dir.join(BINCODE_PUBLIC_VALUES),
+        zk_rust_io::public_values(),
+    )
+    .expect("Failed to save public values");
+    std::fs::write(
+        output_dir.join(RISC0_PUBLIC_VALUES),
+        zk_rust_io::risc0_public_values(),
+    )
+    .expect("Failed to save public values");
+
+    output();
+}
+
+fn input() {
+    // INPUT //
+}
+
+#[allow(unused_variables)]
+fn read_inputs(inputs: &mut inputs::Inputs) {
+    // READ INPUTS //
+}
+
+fn output() {
+    // OUTPUT //
+}
@@ -0,0 +1,62 @@
+#![cfg_attr(target_arch = "riscv32", no_std, no_main)]
+extern crate alloc;
+#[allow(unused_imports)]
+use alloc::{
+    boxed::Box, format, string::{String, ToString},
+    vec, vec::Vec,
+};
+#[allow(unused_imports)]
+use nexus_rt::println;
+pub mod zkrust {
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    struct Io {
+        input: RefCell<(Vec<u8>, usize)>,
+        output: RefCell<Vec<u8>>,
+    }
+    unsafe impl Sync for Io {}
+    static IO: Io = Io {
+        input: RefCell::new((Vec::new(), 0)),
+        output: RefCell::new(Vec::new()),
+    };
+    #[nexus_rt::main]
+    #[nexus_rt::private_input(input)]
+    fn entry(input: Vec<u8>) -> Vec<u8> {
+        *IO.input.borrow_mut() = (input, 0);
+        super::main();
+        IO.output.take()
+    }
+    pub fn read<T: serde::de::DeserializeOwned>() -> T {
+        let mut input = IO.input.borrow_mut();
+        let (bytes, position) = &mut *input;
+        let (value, read) = bincode::serde::decode_from_slice(
+                &bytes[*position..],
+                bincode::config::legacy(),
+            )
+            .expect("Failed to read input");
+        *position += read;
+        value
+    }
+    pub fn commit<T: serde::Serialize>(value: &T) {
+        let bytes = bincode::serde::encode_to_vec(value, bincode::config::legacy())
+            .expect("Failed to commit");
+        IO.output.borrow_mut().extend(bytes);
+    }
+}
+use zk_rust_io;
+pub fn main() {
+    println!("cycle-tracker-report-start: {}", env!("CARGO_PKG_NAME"));
+    let n: u32 = crate::zkrust::read();
+    crate::zkrust::commit(&n);
+    let mut a: u32 = 0;
+    let mut b: u32 = 1;
+    for _ in 0..n {
+        let mut c = a + b;
+        c %= 7919;
+        a = b;
+        b = c;
+    }
+    crate::zkrust::commit(&a);
+    crate::zkrust::commit(&b);
+    println!("cycle-tracker-report-end: {}", env!("CARGO_PKG_NAME"));
+}
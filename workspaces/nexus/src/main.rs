@@ -0,0 +1,175 @@
+use nexus_sdk::{
+    compile::{cargo::CargoPackager, Compile, Compiler},
+    stwo::seq::Stwo,
+    ByGuestCompilation, KnownExitCodes, Local, Prover, Verifiable, Viewable,
+};
+use serde::{de::DeserializeOwned, Serialize};
+mod inputs;
+mod metrics;
+use metrics::{ExecutionReport, MetricsCollector, NexusMetrics};
+use tracing::{error, info};
+
+/// Package of the guest crate, compiled by the host when it runs.
+const GUEST_PACKAGE: &str = "method";
+
+/// Values read by the guest, serialized with bincode and passed as its private input.
+#[derive(Default, Clone)]
+pub struct NexusStdin {
+    pub bytes: Vec<u8>,
+}
+
+impl NexusStdin {
+    pub fn write<T: Serialize>(&mut self, value: &T) {
+        bincode::serialize_into(&mut self.bytes, value).expect("Failed to serialize input");
+    }
+}
+
+/// Values committed by the guest, returned as its public output.
+pub struct PublicValues {
+    cursor: std::io::Cursor<Vec<u8>>,
+}
+
+impl PublicValues {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            cursor: std::io::Cursor::new(bytes),
+        }
+    }
+
+    /// Deserializes the next committed value.
+    pub fn read<T: DeserializeOwned>(&mut self) -> T {
+        bincode::deserialize_from(&mut self.cursor).expect("Failed to read public values")
+    }
+}
+
+/// Exit code of the host when a proof it generated fails verification, distinct from the exit
+/// code of panics.
+const VERIFICATION_FAILED: i32 = 103;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // Directory the proof artifacts are saved to
+    let output_dir = std::path::PathBuf::from(args[1].clone());
+    // Only run the guest with `execute`, otherwise Nexus only generates `compressed` proofs
+    let mode = args.get(2).map_or("compressed", String::as_str);
+    let execute_only = mode == "execute";
+    // Setup the logger.
+    tracing_subscriber::fmt::init();
+
+    let mut metrics = NexusMetrics::default();
+    let mut core_timer = MetricsCollector::new();
+
+    // Setup the inputs and set as mutable to allow for template code to access it if needed
+    let mut stdin = NexusStdin::default();
+    match args.get(3) {
+        // Values given to zkRust with `--input`, instead of the ones written by `input()`
+        Some(input_path) => {
+            let mut inputs = inputs::Inputs::open(input_path, &args[4]);
+            read_inputs(&mut inputs, &mut stdin);
+        }
+        None => input(&mut stdin),
+    }
+
+    let mut compiler = Compiler::<CargoPackager>::new(GUEST_PACKAGE);
+    let prover: Stwo<Local> = Stwo::compile(&mut compiler).expect("Failed to compile the guest");
+    let elf = prover.elf.clone();
+    // Nexus proves the whole execution at once
+    metrics.num_segments = 1;
+
+    if execute_only {
+        let mut execution_timer = MetricsCollector::new();
+        execution_timer.start_timing();
+        let view = prover
+            .run_with_input::<Vec<u8>, ()>(&stdin.bytes, &())
+            .expect("Failed to execute the guest");
+        let execution_duration = execution_timer.elapsed().unwrap();
+        let public_values = view
+            .public_output::<Vec<u8>>()
+            .expect("Failed to read public values");
+        output(&mut PublicValues::new(public_values));
+
+        let report = ExecutionReport {
+            cycles: metrics.cycles,
+            num_segments: metrics.num_segments,
+            execution_duration,
+            syscalls: metrics.syscalls.clone(),
+            precompiles: metrics.precompiles.clone(),
+        };
+        std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+        metrics::write_execution_report(&report, &output_dir)
+            .expect("Failed to save execution report");
+        return;
+    }
+    metrics.proof_mode = mode.to_string();
+
+    // Generate proof
+    core_timer.start_timing();
+    let (view, proof) = prover
+        .prove_with_input::<Vec<u8>, ()>(&stdin.bytes, &())
+        .expect("Failed to generate Nexus proof");
+    metrics.core_prove_duration = core_timer.elapsed().unwrap();
+    metrics.core_proof_size = proof.size_estimate();
+    metrics.recursive_proof_size = metrics.core_proof_size;
+    let public_values = view
+        .public_output::<Vec<u8>>()
+        .expect("Failed to read public values");
+
+    // Verify proof
+    core_timer.start_timing();
+    check_verified(
+        proof.verify_expected::<(), Vec<u8>>(
+            &(),
+            KnownExitCodes::ExitSuccess as u32,
+            &public_values,
+            &elf,
+            &[],
+        ),
+        "Nexus",
+    );
+    metrics.core_verify_duration = core_timer.elapsed().unwrap();
+
+    std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+
+    // Save proof artifacts
+    std::fs::write(
+        output_dir.join("nexus.proof"),
+        bincode::serialize(&proof).unwrap(),
+    )
+    .expect("Failed to save Nexus Proof file");
+    std::fs::write(output_dir.join("nexus.elf"), elf.to_bytes())
+        .expect("Failed to create Nexus elf file");
+    std::fs::write(output_dir.join("nexus.pub"), &public_values)
+        .expect("Failed to save Nexus public input");
+
+    output(&mut PublicValues::new(public_values));
+
+    // Save metrics
+    info!("Attempting to save metrics...");
+    match metrics::write_metrics(&metrics, &output_dir) {
+        Ok(_) => info!("Successfully saved metrics"),
+        Err(e) => error!("Failed to save metrics: {}", e),
+    };
+}
+
+#[allow(unused_variables)]
+fn input(stdin: &mut NexusStdin) {
+    // INPUT //
+}
+
+#[allow(unused_variables)]
+fn read_inputs(inputs: &mut inputs::Inputs, stdin: &mut NexusStdin) {
+    // READ INPUTS //
+}
+
+#[allow(unused_variables)]
+fn output(public_values: &mut PublicValues) {
+    // OUTPUT //
+}
+
+/// Exits with `VERIFICATION_FAILED` if verifying the `stage` proof failed.
+fn check_verified<T, E: std::fmt::Debug>(result: Result<T, E>, stage: &str) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Failed to verify {} proof: {:?}", stage, e);
+        std::process::exit(VERIFICATION_FAILED)
+    })
+}
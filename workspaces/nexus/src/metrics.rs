@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// Invocations of a syscall, Nexus does not report the syscalls of an execution.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SyscallStats {
+    pub count: u64,
+    pub cycles: Option<u64>,
+}
+
+/// Metrics of a Nexus proof, with the fields of the SP1 metrics. Nexus proofs are not
+/// recursively compressed, so the compressed proof is the core proof. Nexus does not report the
+/// cycle count of an execution, `cycles` is left unset.
+#[derive(Default, Serialize, Deserialize)]
+pub struct NexusMetrics {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub core_proof_size: usize,
+    pub recursive_proof_size: usize,
+    pub core_prove_duration: Duration,
+    pub core_verify_duration: Duration,
+    pub compress_prove_duration: Duration,
+    pub compress_verify_duration: Duration,
+    pub proof_mode: String,
+    pub wrap_proof_size: usize,
+    pub wrap_prove_duration: Duration,
+    pub wrap_verify_duration: Duration,
+    pub mock: bool,
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+pub struct MetricsCollector {
+    start_time: Option<Instant>,
+}
+
+impl MetricsCollector {
+    pub fn new() -> Self {
+        Self { start_time: None }
+    }
+
+    pub fn start_timing(&mut self) {
+        self.start_time = Some(Instant::now());
+    }
+
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.start_time.map(|t| t.elapsed())
+    }
+}
+
+pub fn write_metrics(metrics: &NexusMetrics, output_path: &std::path::Path) -> std::io::Result<()> {
+    info!("About to write metrics");
+
+    let metrics_path = output_path.join("nexus_metrics.json");
+    info!("Full metrics path: {}", metrics_path.display());
+
+    let json = serde_json::to_string_pretty(metrics)?;
+    info!("Generated JSON: {}", json);
+
+    match std::fs::write(&metrics_path, &json) {
+        Ok(_) => {
+            info!("Successfully wrote metrics to {}", metrics_path.display());
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to write metrics: {}", e);
+            Err(e)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub execution_duration: Duration,
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+pub fn write_execution_report(
+    report: &ExecutionReport,
+    output_path: &std::path::Path,
+) -> std::io::Result<()> {
+    let report_path = output_path.join("nexus_execution.json");
+    info!("Writing execution report to {}", report_path.display());
+    std::fs::write(&report_path, serde_json::to_string_pretty(report)?)
+}
@@ -0,0 +1,33 @@
+openvm::entry!(main);
+pub mod zkrust {
+    use core::cell::Cell;
+    struct Revealed(Cell<usize>);
+    unsafe impl Sync for Revealed {}
+    static REVEALED: Revealed = Revealed(Cell::new(0));
+    pub fn commit<T: serde::Serialize>(value: &T) {
+        let words = openvm::serde::to_vec(value).expect("Failed to commit");
+        let start = REVEALED.0.get();
+        for (i, word) in words.iter().enumerate() {
+            openvm::io::reveal_u32(*word, start + i + 1);
+        }
+        REVEALED.0.set(start + words.len());
+        openvm::io::reveal_u32(REVEALED.0.get() as u32, 0);
+    }
+}
+use zk_rust_io;
+pub fn main() {
+    println!("cycle-tracker-report-start: {}", env!("CARGO_PKG_NAME"));
+    let n: u32 = openvm::io::read();
+    crate::zkrust::commit(&n);
+    let mut a: u32 = 0;
+    let mut b: u32 = 1;
+    for _ in 0..n {
+        let mut c = a + b;
+        c %= 7919;
+        a = b;
+        b = c;
+    }
+    crate::zkrust::commit(&a);
+    crate::zkrust::commit(&b);
+    println!("cycle-tracker-report-end: {}", env!("CARGO_PKG_NAME"));
+}
@@ -0,0 +1,43 @@
+use openvm::serde::Deserializer;
+use serde::de::DeserializeOwned;
+
+/// Reads the values revealed by the guest one after the other, as SP1 reads its public values.
+/// The guest reveals the number of words committed in the first word of the public values,
+/// followed by the committed values padded to whole words.
+pub struct JournalReader {
+    words: Vec<u32>,
+    position: usize,
+}
+
+impl JournalReader {
+    pub fn new(public_values: &[u8]) -> Self {
+        let mut words = public_values
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            })
+            .collect::<Vec<_>>();
+        let len = words.first().copied().unwrap_or_default() as usize;
+        words.truncate(len + 1);
+        Self { words, position: 1 }
+    }
+
+    /// Committed values, without the word count and the unused public values.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.words[1..]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    /// Deserializes the next committed value as a `T`.
+    pub fn read<T: DeserializeOwned>(&mut self) -> T {
+        let mut words = &self.words[self.position..];
+        let value = T::deserialize(&mut Deserializer::new(&mut words))
+            .expect("Failed to decode value from public values");
+        self.position = self.words.len() - words.len();
+        value
+    }
+}
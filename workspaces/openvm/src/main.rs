@@ -0,0 +1,174 @@
+use openvm_build::GuestOptions;
+use openvm_circuit::arch::{SystemConfig, VmExecutor};
+use openvm_sdk::{
+    config::{AppConfig, SdkVmConfig},
+    Sdk, StdIn,
+};
+use openvm_stark_sdk::{config::FriParameters, openvm_stark_backend::p3_field::PrimeField32};
+use std::sync::Arc;
+mod inputs;
+mod journal;
+mod metrics;
+use journal::JournalReader;
+use metrics::{ExecutionReport, MetricsCollector, OpenVmMetrics};
+use tracing::{error, info};
+
+/// Directory of the guest crate, built by the host when it runs.
+const GUEST_DIR: &str = "guest";
+/// ELF of the guest built by the host, relative to the workspace.
+const GUEST_ELF: &str = "guest/target/riscv32im-risc0-zkvm-elf/release/method";
+/// Bytes of public values the guest can reveal, including the word count written by zkRust.
+const NUM_PUBLIC_VALUES: usize = 1024;
+/// Log of the FRI blowup factor of the application proofs.
+const APP_LOG_BLOWUP: usize = 2;
+
+/// Exit code of the host when a proof it generated fails verification, distinct from the exit
+/// code of panics.
+const VERIFICATION_FAILED: i32 = 103;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // Directory the proof artifacts are saved to
+    let output_dir = std::path::PathBuf::from(args[1].clone());
+    // Only run the executor with `execute`, otherwise OpenVM only generates `core` proofs
+    let mode = args.get(2).map_or("core", String::as_str);
+    let execute_only = mode == "execute";
+    // Setup the logger.
+    tracing_subscriber::fmt::init();
+
+    let mut metrics = OpenVmMetrics::default();
+    let mut core_timer = MetricsCollector::new();
+
+    // Setup the inputs and set as mutable to allow for template code to access it if needed
+    let mut stdin = StdIn::default();
+    match args.get(3) {
+        // Values given to zkRust with `--input`, instead of the ones written by `input()`
+        Some(input_path) => {
+            let mut inputs = inputs::Inputs::open(input_path, &args[4]);
+            read_inputs(&mut inputs, &mut stdin);
+        }
+        None => input(&mut stdin),
+    }
+
+    let sdk = Sdk::new();
+    let vm_config = SdkVmConfig::builder()
+        .system(
+            SystemConfig::default()
+                .with_public_values(NUM_PUBLIC_VALUES)
+                .into(),
+        )
+        .rv32i(Default::default())
+        .rv32m(Default::default())
+        .io(Default::default())
+        .build();
+    let elf = sdk
+        .build(GuestOptions::default(), GUEST_DIR, &Default::default())
+        .expect("Failed to build the guest");
+    let exe = sdk
+        .transpile(elf, vm_config.transpiler())
+        .expect("Failed to transpile the guest");
+
+    // First run executor to get cycle count and segments
+    let mut execution_timer = MetricsCollector::new();
+    execution_timer.start_timing();
+    let segments = VmExecutor::new(vm_config.clone())
+        .execute_segments(exe.clone(), stdin.clone())
+        .expect("Failed to execute the guest");
+    let execution_duration = execution_timer.elapsed().unwrap();
+    metrics.cycles = segments
+        .iter()
+        .map(|segment| segment.metrics.cycle_count as u64)
+        .sum();
+    metrics.num_segments = segments.len();
+
+    let public_values: Vec<u8> = sdk
+        .execute(exe.clone(), vm_config.clone(), stdin.clone())
+        .expect("Failed to execute the guest")
+        .iter()
+        .map(|value| value.as_canonical_u32() as u8)
+        .collect();
+
+    if execute_only {
+        output(&mut JournalReader::new(&public_values));
+
+        let report = ExecutionReport {
+            cycles: metrics.cycles,
+            num_segments: metrics.num_segments,
+            execution_duration,
+            syscalls: metrics.syscalls.clone(),
+            precompiles: metrics.precompiles.clone(),
+        };
+        std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+        metrics::write_execution_report(&report, &output_dir)
+            .expect("Failed to save execution report");
+        return;
+    }
+
+    // Setup the prover
+    let app_fri_params = FriParameters::standard_with_100_bits_conjectured_security(APP_LOG_BLOWUP);
+    let app_config = AppConfig::new(app_fri_params, vm_config);
+    let app_committed_exe = sdk
+        .commit_app_exe(app_fri_params, exe)
+        .expect("Failed to commit the guest");
+    let app_pk = Arc::new(sdk.app_keygen(app_config).expect("Failed to generate keys"));
+    let app_vk = app_pk.get_app_vk();
+    metrics.proof_mode = mode.to_string();
+
+    // Generate core proof
+    core_timer.start_timing();
+    let proof = sdk
+        .generate_app_proof(app_pk.clone(), app_committed_exe, stdin)
+        .expect("Failed to generate OpenVM proof");
+    metrics.core_prove_duration = core_timer.elapsed().unwrap();
+
+    let proof_bytes = bincode::serialize(&proof).unwrap();
+    metrics.core_proof_size = proof_bytes.len();
+
+    // Verify core proof
+    core_timer.start_timing();
+    check_verified(sdk.verify_app_proof(&app_vk, &proof), "OpenVM");
+    metrics.core_verify_duration = core_timer.elapsed().unwrap();
+
+    let mut journal = JournalReader::new(&public_values);
+
+    // Save proof artifacts
+    std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+    std::fs::write(output_dir.join("openvm.proof"), proof_bytes)
+        .expect("Failed to save OpenVM Proof file");
+    std::fs::copy(GUEST_ELF, output_dir.join("openvm.elf"))
+        .expect("Failed to create OpenVM elf file");
+    std::fs::write(output_dir.join("openvm.pub"), journal.bytes())
+        .expect("Failed to save OpenVM public input");
+
+    output(&mut journal);
+
+    // Save metrics
+    info!("Attempting to save metrics...");
+    match metrics::write_metrics(&metrics, &output_dir) {
+        Ok(_) => info!("Successfully saved metrics"),
+        Err(e) => error!("Failed to save metrics: {}", e),
+    };
+}
+
+#[allow(unused_variables)]
+fn input(stdin: &mut StdIn) {
+    // INPUT //
+}
+
+#[allow(unused_variables)]
+fn read_inputs(inputs: &mut inputs::Inputs, stdin: &mut StdIn) {
+    // READ INPUTS //
+}
+
+#[allow(unused_variables)]
+fn output(journal: &mut JournalReader) {
+    // OUTPUT //
+}
+
+/// Exits with `VERIFICATION_FAILED` if verifying the `stage` proof failed.
+fn check_verified<T, E: std::fmt::Debug>(result: Result<T, E>, stage: &str) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Failed to verify {} proof: {:?}", stage, e);
+        std::process::exit(VERIFICATION_FAILED)
+    })
+}
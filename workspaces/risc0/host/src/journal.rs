@@ -0,0 +1,34 @@
+use risc0_zkvm::{serde::Deserializer, Journal};
+use serde::de::DeserializeOwned;
+
+/// Reads the values committed to the journal one after the other, as SP1 reads its public
+/// values, instead of decoding the journal from its start on every read.
+pub struct JournalReader {
+    words: Vec<u32>,
+    position: usize,
+}
+
+impl JournalReader {
+    pub fn new(journal: &Journal) -> Self {
+        // Committed values are padded to whole words
+        let words = journal
+            .bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            })
+            .collect();
+        Self { words, position: 0 }
+    }
+
+    /// Deserializes the next committed value as a `T`.
+    pub fn read<T: DeserializeOwned>(&mut self) -> T {
+        let mut words = &self.words[self.position..];
+        let value = T::deserialize(&mut Deserializer::new(&mut words))
+            .expect("Failed to decode value from journal");
+        self.position = self.words.len() - words.len();
+        value
+    }
+}
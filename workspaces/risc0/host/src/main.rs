@@ -1,31 +1,85 @@
 // These constants represent the RISC-V ELF and the image ID generated by risc0-build.
 // The ELF is used for proving and the ID is used for verification.
 use methods::{METHOD_ELF, METHOD_ID};
-use risc0_zkvm::{get_prover_server, ExecutorEnv, ExecutorImpl, ProverOpts, VerifierContext};
+use risc0_zkvm::{
+    get_prover_server,
+    sha::{Digest, Digestible},
+    ExecutorEnv, ExecutorEnvBuilder, ExecutorImpl, Journal, ProverOpts, VerifierContext,
+};
 use std::time::Instant;
+mod inputs;
+mod journal;
 mod metrics;
-use metrics::{MetricsCollector, Risc0Metrics};
+use journal::JournalReader;
+use metrics::{ExecutionReport, MetricsCollector, Risc0Metrics};
+
+/// Exit code of the host when a proof it generated fails verification, distinct from the exit
+/// code of panics.
+const VERIFICATION_FAILED: i32 = 103;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    let current_dir = std::path::PathBuf::from(args[1].clone());
+    // Directory the proof artifacts are saved to
+    let output_dir = std::path::PathBuf::from(args[1].clone());
+    // Only run the executor with `execute`, or the stage of the saved proof: `core`,
+    // `compressed` or `groth16`
+    let mode = args.get(2).map_or("compressed", String::as_str);
+    let execute_only = mode == "execute";
 
     let mut metrics = Risc0Metrics::default();
     let mut core_timer = MetricsCollector::new();
     let mut compress_timer = MetricsCollector::new();
+    let mut wrap_timer = MetricsCollector::new();
 
-    // INPUT //
-    let env = ExecutorEnv::builder().build().unwrap();
+    // Values written to the guest, in the order they are read
+    let mut builder = ExecutorEnv::builder();
+    match args.get(3) {
+        // Values given to zkRust with `--input`, instead of the ones written by `input()`
+        Some(input_path) => {
+            let mut inputs = inputs::Inputs::open(input_path, &args[4]);
+            read_inputs(&mut inputs, &mut builder);
+        }
+        None => input(&mut builder),
+    }
+    let env = builder.build().unwrap();
 
     // First run executor to get cycle count and segments
+    let mut execution_timer = MetricsCollector::new();
+    execution_timer.start_timing();
     let mut exec = ExecutorImpl::from_elf(env, METHOD_ELF).unwrap();
     let session = exec.run().unwrap();
+    let execution_duration = execution_timer.elapsed().unwrap();
     metrics.cycles = session.user_cycles;
     metrics.num_segments = session.segments.len();
+    (metrics.syscalls, metrics.precompiles) = metrics::syscall_stats(&session);
+
+    if execute_only {
+        output(
+            session
+                .journal
+                .as_ref()
+                .expect("Execution produced no journal"),
+        );
+
+        let report = ExecutionReport {
+            cycles: metrics.cycles,
+            num_segments: metrics.num_segments,
+            execution_duration,
+            syscalls: metrics.syscalls.clone(),
+            precompiles: metrics.precompiles.clone(),
+        };
+        std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+        metrics::write_execution_report(&report, &output_dir)
+            .expect("Failed to save execution report");
+        return;
+    }
 
     // Setup the prover
     let opts = ProverOpts::default();
     let prover = get_prover_server(&opts).unwrap();
+    metrics.proof_mode = mode.to_string();
+    // Fake receipts are generated when zkRust runs with `--mock`, they have no seals
+    metrics.mock = risc0_zkvm::is_dev_mode();
 
     // Generate core proof
     core_timer.start_timing();
@@ -34,57 +88,114 @@ fn main() {
     metrics.core_prove_duration = core_timer.elapsed().unwrap();
 
     let receipt = info.receipt;
-    let composite_receipt = receipt.inner.composite().unwrap();
-    metrics.core_proof_size = composite_receipt
-        .segments
-        .iter()
-        .map(|s| s.seal.len() * 4)
-        .sum();
+    metrics.core_proof_size = receipt.inner.composite().map_or(0, |composite_receipt| {
+        composite_receipt
+            .segments
+            .iter()
+            .map(|s| s.seal.len() * 4)
+            .sum()
+    });
 
     // Verify core proof
     core_timer.start_timing();
-    receipt.verify(METHOD_ID).unwrap();
+    check_verified(receipt.verify(METHOD_ID), "core");
     metrics.core_verify_duration = core_timer.elapsed().unwrap();
 
-    // Generate compressed/recursive proof
-    compress_timer.start_timing();
-    let compressed_proof = prover.compress(&ProverOpts::succinct(), &receipt).unwrap();
-    metrics.compress_prove_duration = compress_timer.elapsed().unwrap();
+    // Receipt saved to the output directory, the last stage generated for the proof mode
+    let mut saved_receipt = receipt;
 
-    // Verify compressed proof
-    compress_timer.start_timing();
-    compressed_proof.verify(METHOD_ID).unwrap();
-    metrics.compress_verify_duration = compress_timer.elapsed().unwrap();
+    if mode != "core" {
+        // Generate compressed/recursive proof
+        compress_timer.start_timing();
+        let compressed_proof = prover
+            .compress(&ProverOpts::succinct(), &saved_receipt)
+            .unwrap();
+        metrics.compress_prove_duration = compress_timer.elapsed().unwrap();
 
-    // Get compressed proof size
-    let succinct_receipt = compressed_proof.inner.succinct().unwrap();
-    metrics.recursive_proof_size = succinct_receipt.seal.len() * 4;
+        // Verify compressed proof
+        compress_timer.start_timing();
+        check_verified(compressed_proof.verify(METHOD_ID), "compressed");
+        metrics.compress_verify_duration = compress_timer.elapsed().unwrap();
 
-    // OUTPUT //
+        // Get compressed proof size
+        metrics.recursive_proof_size = compressed_proof
+            .inner
+            .succinct()
+            .map_or(0, |succinct_receipt| succinct_receipt.seal.len() * 4);
+        saved_receipt = compressed_proof;
+    }
+
+    std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+
+    if mode == "groth16" {
+        // Wrap the succinct receipt in a Groth16 SNARK accepted by the RISC0 EVM verifier contracts
+        wrap_timer.start_timing();
+        let wrapped = prover
+            .compress(&ProverOpts::groth16(), &saved_receipt)
+            .unwrap();
+        metrics.wrap_prove_duration = wrap_timer.elapsed().unwrap();
+
+        // Verify wrapped proof
+        wrap_timer.start_timing();
+        check_verified(wrapped.verify(METHOD_ID), "wrapped");
+        metrics.wrap_verify_duration = wrap_timer.elapsed().unwrap();
+
+        // Arguments of `IRiscZeroVerifier.verify`, the seal is prefixed with the selector of the
+        // verifier parameters
+        let seal = wrapped
+            .inner
+            .groth16()
+            .map_or(Vec::new(), |groth16_receipt| {
+                [
+                    &groth16_receipt.verifier_parameters.as_bytes()[..4],
+                    groth16_receipt.seal.as_slice(),
+                ]
+                .concat()
+            });
+        metrics.wrap_proof_size = seal.len();
+        let calldata = serde_json::json!({
+            "seal": format!("0x{}", hex::encode(&seal)),
+            "image_id": format!("0x{}", hex::encode(Digest::from(METHOD_ID).as_bytes())),
+            "journal_digest": format!("0x{}", hex::encode(wrapped.journal.digest().as_bytes())),
+        });
+        std::fs::write(
+            output_dir.join("risc0_calldata.json"),
+            serde_json::to_string_pretty(&calldata).unwrap(),
+        )
+        .expect("Failed to save Risc0 calldata");
+        saved_receipt = wrapped;
+    }
+
+    let receipt = saved_receipt;
+    output(&receipt.journal);
 
     // Save proof artifacts
-    std::fs::create_dir_all(current_dir.join("proof_data/risc0"))
-        .expect("Failed to create proof_data/risc0");
     let serialized = bincode::serialize(&receipt).unwrap();
-    std::fs::write(
-        &current_dir.join("proof_data/risc0/risc0.proof"),
-        &serialized,
-    )
-    .expect("Failed to create Risc0 proof file");
-    std::fs::write(
-        &current_dir.join("proof_data/risc0/risc0.imageid"),
-        &convert(&METHOD_ID),
-    )
-    .expect("Failed to create Risc0 Image ID file");
-    std::fs::write(
-        &current_dir.join("proof_data/risc0/risc0.pub"),
-        &receipt.journal,
-    )
-    .expect("Failed to create Risc0 public input file");
+    std::fs::write(output_dir.join("risc0.proof"), &serialized)
+        .expect("Failed to create Risc0 proof file");
+    std::fs::write(output_dir.join("risc0.imageid"), &convert(&METHOD_ID))
+        .expect("Failed to create Risc0 Image ID file");
+    std::fs::write(output_dir.join("risc0.pub"), &receipt.journal)
+        .expect("Failed to create Risc0 public input file");
 
     // Save metrics
-    metrics::write_metrics(&metrics, &current_dir.join("proof_data/risc0"))
-        .expect("Failed to save metrics");
+    metrics::write_metrics(&metrics, &output_dir).expect("Failed to save metrics");
+}
+
+#[allow(unused_variables)]
+fn input(builder: &mut ExecutorEnvBuilder) {
+    // INPUT //
+}
+
+#[allow(unused_variables)]
+fn read_inputs(inputs: &mut inputs::Inputs, builder: &mut ExecutorEnvBuilder) {
+    // READ INPUTS //
+}
+
+#[allow(unused_variables, unused_mut)]
+fn output(journal: &Journal) {
+    let mut journal = JournalReader::new(journal);
+    // OUTPUT //
 }
 
 pub fn convert(data: &[u32; 8]) -> [u8; 32] {
@@ -94,3 +205,11 @@ pub fn convert(data: &[u32; 8]) -> [u8; 32] {
     }
     res
 }
+
+/// Exits with `VERIFICATION_FAILED` if verifying the `stage` proof failed.
+fn check_verified<T, E: std::fmt::Debug>(result: Result<T, E>, stage: &str) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Failed to verify {} proof: {:?}", stage, e);
+        std::process::exit(VERIFICATION_FAILED)
+    })
+}
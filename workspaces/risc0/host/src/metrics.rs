@@ -1,6 +1,17 @@
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
 
+/// Ecalls of the guest accelerated by precompile circuits
+const PRECOMPILE_ECALLS: [&str; 2] = ["Sha2", "BigInt"];
+
+/// Invocations of an ecall and the cycles spent in them.
+#[derive(Clone, Default, Serialize)]
+pub struct SyscallStats {
+    pub count: u64,
+    pub cycles: Option<u64>,
+}
+
 #[derive(Default, Serialize)]
 pub struct Risc0Metrics {
     pub cycles: u64,
@@ -11,6 +22,13 @@ pub struct Risc0Metrics {
     pub core_verify_duration: Duration,
     pub compress_prove_duration: Duration,
     pub compress_verify_duration: Duration,
+    pub proof_mode: String,
+    pub wrap_proof_size: usize,
+    pub wrap_prove_duration: Duration,
+    pub wrap_verify_duration: Duration,
+    pub mock: bool,
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    pub precompiles: BTreeMap<String, SyscallStats>,
 }
 
 pub struct MetricsCollector {
@@ -31,7 +49,50 @@ impl MetricsCollector {
     }
 }
 
+/// Ecalls invoked by a session, and the precompiles among them, by name.
+pub fn syscall_stats(
+    session: &risc0_zkvm::Session,
+) -> (
+    BTreeMap<String, SyscallStats>,
+    BTreeMap<String, SyscallStats>,
+) {
+    let syscalls: BTreeMap<String, SyscallStats> = session
+        .ecall_metrics
+        .iter()
+        .map(|(kind, metric)| {
+            let stats = SyscallStats {
+                count: metric.count,
+                cycles: Some(metric.cycles),
+            };
+            (format!("{:?}", kind), stats)
+        })
+        .collect();
+    let precompiles = syscalls
+        .iter()
+        .filter(|(name, _)| PRECOMPILE_ECALLS.contains(&name.as_str()))
+        .map(|(name, stats)| (name.clone(), stats.clone()))
+        .collect();
+    (syscalls, precompiles)
+}
+
 pub fn write_metrics(metrics: &Risc0Metrics, output_path: &std::path::Path) -> std::io::Result<()> {
     let json = serde_json::to_string_pretty(metrics)?;
     std::fs::write(output_path.join("risc0_metrics.json"), json)
 }
+
+#[derive(Serialize)]
+pub struct ExecutionReport {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub execution_duration: Duration,
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+pub fn write_execution_report(
+    report: &ExecutionReport,
+    output_path: &std::path::Path,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(report)?;
+    std::fs::write(output_path.join("risc0_execution.json"), json)
+}
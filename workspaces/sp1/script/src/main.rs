@@ -1,39 +1,79 @@
-use sp1_sdk::{ProverClient, SP1Stdin};
+use sp1_sdk::{include_elf, ProverClient, SP1PublicValues, SP1Stdin};
+mod inputs;
 mod metrics;
-use metrics::{MetricsCollector, SP1Metrics};
+use metrics::{ExecutionReport, MetricsCollector, SP1Metrics};
 use tracing::{error, info};
 
 /// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
 ///
-/// This file is generated by running `cargo prove build` inside the `program` directory.
-pub const METHOD_ELF: &[u8] = include_bytes!(
-    "../../program/target/elf-compilation/riscv32im-succinct-zkvm-elf/release/method"
-);
+/// This file is built from the `program` directory by `build.rs`, within the cargo target
+/// directory of the script.
+pub const METHOD_ELF: &[u8] = include_elf!("method");
+
+/// Exit code of the host when a proof it generated fails verification, distinct from the exit
+/// code of panics.
+const VERIFICATION_FAILED: i32 = 103;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    let current_dir = std::path::PathBuf::from(args[1].clone());
+    // Directory the proof artifacts are saved to
+    let output_dir = std::path::PathBuf::from(args[1].clone());
+    // Only run the executor with `execute`, or the stage of the saved proof: `core`,
+    // `compressed`, `groth16` or `plonk`
+    let mode = args.get(2).map_or("compressed", String::as_str);
+    let execute_only = mode == "execute";
     // Setup the logger.
     sp1_sdk::utils::setup_logger();
 
     let mut metrics = SP1Metrics::default();
     let mut core_timer = MetricsCollector::new();
     let mut compress_timer = MetricsCollector::new();
+    let mut wrap_timer = MetricsCollector::new();
 
     // Setup the inputs and set as mutable to allow for template code to access it if needed
     let mut stdin = SP1Stdin::new();
-
-    // INPUT //
+    match args.get(3) {
+        // Values given to zkRust with `--input`, instead of the ones written by `input()`
+        Some(input_path) => {
+            let mut inputs = inputs::Inputs::open(input_path, &args[4]);
+            read_inputs(&mut inputs, &mut stdin);
+        }
+        None => input(&mut stdin),
+    }
 
     let client = ProverClient::from_env();
-    let (pk, vk) = client.setup(METHOD_ELF);
 
     // First run executor to get cycle count
-    let (_, report) = client.execute(METHOD_ELF, &stdin.clone()).run().unwrap();
+    let mut execution_timer = MetricsCollector::new();
+    execution_timer.start_timing();
+    let (mut public_values, report) = client.execute(METHOD_ELF, &stdin.clone()).run().unwrap();
+    let execution_duration = execution_timer.elapsed().unwrap();
     // Get total cycles from cycle tracker
     metrics.cycles = report.cycle_tracker.iter().map(|(_, cycles)| *cycles).sum();
     // Number of segments is the number of cycle tracking entries
     metrics.num_segments = report.cycle_tracker.len();
+    (metrics.syscalls, metrics.precompiles) = metrics::syscall_stats(&report);
+
+    if execute_only {
+        output(&mut public_values);
+
+        let report = ExecutionReport {
+            cycles: metrics.cycles,
+            num_segments: metrics.num_segments,
+            execution_duration,
+            syscalls: metrics.syscalls.clone(),
+            precompiles: metrics.precompiles.clone(),
+        };
+        std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+        metrics::write_execution_report(&report, &output_dir)
+            .expect("Failed to save execution report");
+        return;
+    }
+
+    let (pk, vk) = client.setup(METHOD_ELF);
+    metrics.proof_mode = mode.to_string();
+    // Mock proofs are generated when zkRust runs with `--mock`
+    metrics.mock = std::env::var("SP1_PROVER").is_ok_and(|prover| prover == "mock");
 
     // Generate uncompressed proof
     core_timer.start_timing();
@@ -47,53 +87,107 @@ fn main() {
 
     // Verify uncompressed proof
     core_timer.start_timing();
-    client
-        .verify(&proof, &vk)
-        .expect("Failed to verify uncompressed proof");
+    check_verified(client.verify(&proof, &vk), "uncompressed");
     metrics.core_verify_duration = core_timer.elapsed().unwrap();
 
-    // Generate compressed proof
-    compress_timer.start_timing();
-    let compressed = client
-        .prove(&pk, &stdin)
-        .compressed() // Enable compression
-        .run()
-        .unwrap();
-    metrics.compress_prove_duration = compress_timer.elapsed().unwrap();
-
-    // Get compressed proof size
-    let compressed_bytes = bincode::serialize(&compressed).unwrap();
-    metrics.recursive_proof_size = compressed_bytes.len();
-
-    // Verify compressed proof
-    compress_timer.start_timing();
-    client
-        .verify(&compressed, &vk)
-        .expect("Failed to verify compressed proof");
-    metrics.compress_verify_duration = compress_timer.elapsed().unwrap();
-
-    // OUTPUT //
+    // Proof saved to the output directory, the last stage generated for the proof mode
+    let mut saved_proof = core_bytes;
+
+    // The SDK proves the compressed stage again when wrapping, so it is only proven on its own for
+    // compressed proofs
+    if mode == "compressed" {
+        // Generate compressed proof
+        compress_timer.start_timing();
+        let compressed = client
+            .prove(&pk, &stdin)
+            .compressed() // Enable compression
+            .run()
+            .unwrap();
+        metrics.compress_prove_duration = compress_timer.elapsed().unwrap();
+
+        // Get compressed proof size
+        let compressed_bytes = bincode::serialize(&compressed).unwrap();
+        metrics.recursive_proof_size = compressed_bytes.len();
+
+        // Verify compressed proof
+        compress_timer.start_timing();
+        check_verified(client.verify(&compressed, &vk), "compressed");
+        metrics.compress_verify_duration = compress_timer.elapsed().unwrap();
+        saved_proof = compressed_bytes;
+    }
+
+    std::fs::create_dir_all(&output_dir).expect("Failed to create output directory");
+
+    if mode == "groth16" || mode == "plonk" {
+        // Wrap the proof in a SNARK accepted by the SP1 EVM verifier contracts. The SDK proves
+        // the core and compressed stages again before wrapping, so the wrap time is end-to-end.
+        wrap_timer.start_timing();
+        let builder = client.prove(&pk, &stdin);
+        let builder = if mode == "groth16" {
+            builder.groth16()
+        } else {
+            builder.plonk()
+        };
+        let wrapped = builder.run().unwrap();
+        metrics.wrap_prove_duration = wrap_timer.elapsed().unwrap();
+
+        // Verify wrapped proof
+        wrap_timer.start_timing();
+        check_verified(client.verify(&wrapped, &vk), "wrapped");
+        metrics.wrap_verify_duration = wrap_timer.elapsed().unwrap();
+
+        // Arguments of `ISP1Verifier.verifyProof`
+        let proof_bytes = wrapped.bytes();
+        metrics.wrap_proof_size = proof_bytes.len();
+        let calldata = serde_json::json!({
+            "program_vkey": vk.bytes32(),
+            "public_values": format!("0x{}", hex::encode(&wrapped.public_values)),
+            "proof_bytes": format!("0x{}", hex::encode(&proof_bytes)),
+        });
+        std::fs::write(
+            output_dir.join("sp1_calldata.json"),
+            serde_json::to_string_pretty(&calldata).unwrap(),
+        )
+        .expect("Failed to save SP1 calldata");
+        saved_proof = bincode::serialize(&wrapped).unwrap();
+    }
+
+    output(&mut proof.public_values);
 
     // Save proof artifacts
-    std::fs::create_dir_all(current_dir.join("proof_data/sp1"))
-        .expect("Failed to create proof_data/sp1");
-    std::fs::write(
-        current_dir.join("proof_data/sp1/sp1.proof"),
-        compressed_bytes,
-    )
-    .expect("Failed to save SP1 Proof file");
-    std::fs::write(current_dir.join("proof_data/sp1/sp1.elf"), METHOD_ELF)
-        .expect("Failed to create SP1 elf file");
-    std::fs::write(
-        current_dir.join("proof_data/sp1/sp1.pub"),
-        &compressed.public_values,
-    )
-    .expect("Failed to save SP1 public input");
+    std::fs::write(output_dir.join("sp1.proof"), saved_proof)
+        .expect("Failed to save SP1 Proof file");
+    std::fs::write(output_dir.join("sp1.elf"), METHOD_ELF).expect("Failed to create SP1 elf file");
+    std::fs::write(output_dir.join("sp1.pub"), &proof.public_values)
+        .expect("Failed to save SP1 public input");
 
     // Save metrics
     info!("Attempting to save metrics...");
-    match metrics::write_metrics(&metrics, current_dir.join("proof_data/sp1").as_path()) {
+    match metrics::write_metrics(&metrics, &output_dir) {
         Ok(_) => info!("Successfully saved metrics"),
         Err(e) => error!("Failed to save metrics: {}", e),
     };
 }
+
+#[allow(unused_variables)]
+fn input(stdin: &mut SP1Stdin) {
+    // INPUT //
+}
+
+#[allow(unused_variables)]
+fn read_inputs(inputs: &mut inputs::Inputs, stdin: &mut SP1Stdin) {
+    // READ INPUTS //
+}
+
+#[allow(unused_variables)]
+fn output(public_values: &mut SP1PublicValues) {
+    // OUTPUT //
+}
+
+/// Exits with `VERIFICATION_FAILED` if verifying the `stage` proof failed.
+fn check_verified<T, E: std::fmt::Debug>(result: Result<T, E>, stage: &str) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Failed to verify {} proof: {:?}", stage, e);
+        std::process::exit(VERIFICATION_FAILED)
+    })
+}
@@ -1,7 +1,29 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
 use tracing::{error, info};
 
+/// Syscalls of the guest which are not precompiles
+const NON_PRECOMPILE_SYSCALLS: [&str; 10] = [
+    "HALT",
+    "WRITE",
+    "ENTER_UNCONSTRAINED",
+    "EXIT_UNCONSTRAINED",
+    "COMMIT",
+    "COMMIT_DEFERRED_PROOFS",
+    "VERIFY_SP1_PROOF",
+    "HINT_LEN",
+    "HINT_READ",
+    "MPROTECT",
+];
+
+/// Invocations of a syscall, SP1 does not report the cycles spent in each one.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SyscallStats {
+    pub count: u64,
+    pub cycles: Option<u64>,
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct SP1Metrics {
     pub cycles: u64,
@@ -12,6 +34,13 @@ pub struct SP1Metrics {
     pub core_verify_duration: Duration,
     pub compress_prove_duration: Duration,
     pub compress_verify_duration: Duration,
+    pub proof_mode: String,
+    pub wrap_proof_size: usize,
+    pub wrap_prove_duration: Duration,
+    pub wrap_verify_duration: Duration,
+    pub mock: bool,
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    pub precompiles: BTreeMap<String, SyscallStats>,
 }
 
 pub struct MetricsCollector {
@@ -32,6 +61,33 @@ impl MetricsCollector {
     }
 }
 
+/// Syscalls invoked by an execution, and the precompiles among them, by name.
+pub fn syscall_stats(
+    report: &sp1_sdk::ExecutionReport,
+) -> (
+    BTreeMap<String, SyscallStats>,
+    BTreeMap<String, SyscallStats>,
+) {
+    let syscalls: BTreeMap<String, SyscallStats> = report
+        .syscall_counts
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(code, count)| {
+            let stats = SyscallStats {
+                count: *count,
+                cycles: None,
+            };
+            (format!("{:?}", code), stats)
+        })
+        .collect();
+    let precompiles = syscalls
+        .iter()
+        .filter(|(name, _)| !NON_PRECOMPILE_SYSCALLS.contains(&name.as_str()))
+        .map(|(name, stats)| (name.clone(), stats.clone()))
+        .collect();
+    (syscalls, precompiles)
+}
+
 pub fn write_metrics(metrics: &SP1Metrics, output_path: &std::path::Path) -> std::io::Result<()> {
     info!("About to write metrics");
 
@@ -52,3 +108,21 @@ pub fn write_metrics(metrics: &SP1Metrics, output_path: &std::path::Path) -> std
         }
     }
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub cycles: u64,
+    pub num_segments: usize,
+    pub execution_duration: Duration,
+    pub syscalls: BTreeMap<String, SyscallStats>,
+    pub precompiles: BTreeMap<String, SyscallStats>,
+}
+
+pub fn write_execution_report(
+    report: &ExecutionReport,
+    output_path: &std::path::Path,
+) -> std::io::Result<()> {
+    let report_path = output_path.join("sp1_execution.json");
+    info!("Writing execution report to {}", report_path.display());
+    std::fs::write(&report_path, serde_json::to_string_pretty(report)?)
+}
@@ -1,14 +1,79 @@
+//! I/O of zkRust programs. zkRust replaces these calls with the I/O of each zkVM. Natively they
+//! run on thread-local buffers, so programs can be run with `cargo run` and tested with
+//! `cargo test`: `write` queues the values `read` returns, and `commit` appends to the public
+//! values `out` reads back. Commits are also recorded in the encoding of the RISC0 journal, to
+//! compare native runs with the proofs of each zkVM.
+
 use serde::{de::DeserializeOwned, Serialize};
+use std::{cell::RefCell, collections::VecDeque, io::Cursor};
+
+mod risc0;
+
+thread_local! {
+    /// Values written by `input()`, serialized with bincode, in the order they are read.
+    static INPUT: RefCell<VecDeque<Vec<u8>>> = const { RefCell::new(VecDeque::new()) };
+    /// Values committed by `main()`, serialized with bincode, and the position `out()` reads at.
+    static PUBLIC_VALUES: RefCell<Cursor<Vec<u8>>> = const { RefCell::new(Cursor::new(Vec::new())) };
+    /// Values committed by `main()`, in the word-padded encoding of the RISC0 journal.
+    static RISC0_PUBLIC_VALUES: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
 
+/// Reads the next value written with `write`. Panics if every written value was read.
 #[inline(never)]
-pub fn read<T: DeserializeOwned + Default>() -> T {
-    T::default()
+pub fn read<T: DeserializeOwned>() -> T {
+    let bytes = INPUT
+        .with(|input| input.borrow_mut().pop_front())
+        .expect("zk_rust_io::read() called with no value left to read");
+    bincode::deserialize(&bytes).expect("Failed to read input")
 }
+
+/// Appends `value` to the public values.
 #[inline(never)]
-pub fn commit<T: Serialize>(_value: &T) {}
+pub fn commit<T: Serialize>(value: &T) {
+    PUBLIC_VALUES.with(|public_values| {
+        bincode::serialize_into(public_values.borrow_mut().get_mut(), value)
+            .expect("Failed to commit")
+    });
+    let words = risc0::to_words(value).expect("Failed to commit");
+    RISC0_PUBLIC_VALUES.with(|public_values| public_values.borrow_mut().extend(words));
+}
+
+/// Queues `buf` to be returned by `read`.
 #[inline(never)]
-pub fn write<T: Serialize>(_buf: &T) {}
+pub fn write<T: Serialize>(buf: &T) {
+    let bytes = bincode::serialize(buf).expect("Failed to write input");
+    INPUT.with(|input| input.borrow_mut().push_back(bytes));
+}
+
+/// Reads the next committed value, in the order of the commits.
 #[inline(never)]
-pub fn out<T: Default>() -> T{
-    T::default()
+pub fn out<T: DeserializeOwned>() -> T {
+    PUBLIC_VALUES.with(|public_values| {
+        bincode::deserialize_from(&mut *public_values.borrow_mut())
+            .expect("Failed to read public values")
+    })
+}
+
+/// Public values committed so far, serialized with bincode like the SP1 public values.
+pub fn public_values() -> Vec<u8> {
+    PUBLIC_VALUES.with(|public_values| public_values.borrow().get_ref().clone())
+}
+
+/// Public values committed so far, in the word-padded encoding of the RISC0 and OpenVM
+/// journals, as little-endian bytes.
+pub fn risc0_public_values() -> Vec<u8> {
+    RISC0_PUBLIC_VALUES.with(|public_values| {
+        public_values
+            .borrow()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    })
+}
+
+/// Clears the written and committed values of the current thread, to run a program again.
+pub fn reset() {
+    INPUT.with(|input| input.borrow_mut().clear());
+    PUBLIC_VALUES.with(|public_values| *public_values.borrow_mut() = Cursor::new(Vec::new()));
+    RISC0_PUBLIC_VALUES.with(|public_values| public_values.borrow_mut().clear());
 }
@@ -0,0 +1,76 @@
+//! Tests of the native I/O, running a program the way zkRust runs it in a zkVM:
+//! `input()`, `main()` and then `output()`.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Account {
+    name: String,
+    balance: u64,
+}
+
+fn input() {
+    zk_rust_io::write(&10u32);
+    zk_rust_io::write(&Account {
+        name: "alice".to_string(),
+        balance: 100,
+    });
+}
+
+fn main() {
+    let n: u32 = zk_rust_io::read();
+    let mut account: Account = zk_rust_io::read();
+    account.balance += n as u64;
+    zk_rust_io::commit(&n);
+    zk_rust_io::commit(&account);
+}
+
+#[test]
+fn reads_written_values_and_outputs_committed_values() {
+    input();
+    main();
+
+    let n: u32 = zk_rust_io::out();
+    let account: Account = zk_rust_io::out();
+    assert_eq!(n, 10);
+    assert_eq!(
+        account,
+        Account {
+            name: "alice".to_string(),
+            balance: 110,
+        }
+    );
+}
+
+#[test]
+fn public_values_are_bincode_serialized() {
+    input();
+    main();
+
+    let mut expected = bincode::serialize(&10u32).unwrap();
+    expected.extend(bincode::serialize(&("alice", 110u64)).unwrap());
+    assert_eq!(zk_rust_io::public_values(), expected);
+
+    zk_rust_io::reset();
+    assert!(zk_rust_io::public_values().is_empty());
+}
+
+#[test]
+fn risc0_public_values_are_word_padded() {
+    input();
+    main();
+
+    // 10, then "alice" as its length and its bytes padded to two words, then 110 as two words
+    let words: [u32; 6] = [10, 5, u32::from_le_bytes(*b"alic"), u32::from(b'e'), 110, 0];
+    let expected: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+    assert_eq!(zk_rust_io::risc0_public_values(), expected);
+
+    zk_rust_io::reset();
+    assert!(zk_rust_io::risc0_public_values().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "no value left to read")]
+fn read_without_write_panics() {
+    zk_rust_io::read::<u32>();
+}